@@ -0,0 +1,279 @@
+use crate::models::{AlertAction, AlertEvent, AlertSeverity};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Shows desktop notifications. The GUI implements this on top of the notification plugin;
+/// when no implementation is available `Notify` actions are skipped.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, event: &AlertEvent);
+}
+
+/// Run every action configured on a rule for a fired alert.
+/// Slow actions (commands, webhooks) are spawned so the monitor loop never waits on them.
+pub fn dispatch(actions: &[AlertAction], event: &AlertEvent, notifier: Option<&dyn Notifier>) {
+    for action in actions {
+        let result = match action {
+            AlertAction::Notify => {
+                if let Some(notifier) = notifier {
+                    notifier.notify(event);
+                }
+                Ok(())
+            }
+            AlertAction::Command { program, args } => run_command(program, args, event),
+            AlertAction::Webhook { url, headers } => {
+                // Detached: the request completes in the background
+                drop(send_webhook(url.clone(), headers.clone(), event.clone()));
+                Ok(())
+            }
+            AlertAction::LogFile { path } => append_log(Path::new(path), event),
+            AlertAction::Syslog => write_syslog(event),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Alert action for rule '{}' failed: {}", event.rule_name, e);
+        }
+    }
+}
+
+fn severity_label(severity: AlertSeverity) -> &'static str {
+    match severity {
+        AlertSeverity::Info => "info",
+        AlertSeverity::Warning => "warning",
+        AlertSeverity::Critical => "critical",
+    }
+}
+
+/// Expand `{pid}`, `{name}`, `{value}`, `{threshold}`, `{rule}`, `{rule_id}`, `{severity}`,
+/// `{subject}`, `{message}` and `{timestamp}` placeholders in a single pass, so braces
+/// in the substituted values (a process name, a message) are left as they are.
+/// Unknown placeholders are kept verbatim.
+pub fn expand_template(template: &str, event: &AlertEvent) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let token = &rest[open..];
+        let value = token
+            .find('}')
+            .and_then(|close| Some((placeholder(&token[1..close], event)?, close)));
+        match value {
+            Some((value, close)) => {
+                out.push_str(&value);
+                rest = &token[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &token[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholder(key: &str, event: &AlertEvent) -> Option<String> {
+    Some(match key {
+        "pid" => event.pid.map(|p| p.to_string()).unwrap_or_default(),
+        "name" => event.process_name.clone().unwrap_or_default(),
+        "value" => format!("{:.2}", event.value),
+        "threshold" => format!("{:.2}", event.threshold),
+        "rule" => event.rule_name.clone(),
+        "rule_id" => event.rule_id.clone(),
+        "severity" => severity_label(event.severity).to_string(),
+        "subject" => event.subject.clone(),
+        "message" => event.message.clone(),
+        "timestamp" => event.timestamp.to_string(),
+        _ => return None,
+    })
+}
+
+fn run_command(program: &str, args: &[String], event: &AlertEvent) -> Result<(), String> {
+    // Arguments are passed directly to the program (no shell), so expanded values can't inject commands
+    let args: Vec<String> = args.iter().map(|a| expand_template(a, event)).collect();
+
+    let mut child = tokio::process::Command::new(program)
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    let program = program.to_string();
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if !status.success() => {
+                eprintln!("Alert command {} exited with {}", program, status)
+            }
+            Err(e) => eprintln!("Failed to wait for alert command {}: {}", program, e),
+            _ => {}
+        }
+    });
+
+    Ok(())
+}
+
+fn send_webhook(
+    url: String,
+    headers: std::collections::HashMap<String, String>,
+    event: AlertEvent,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut request = client
+            .post(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .json(&event);
+        for (name, value) in &headers {
+            request = request.header(name, value);
+        }
+
+        match request.send().await {
+            Ok(response) if !response.status().is_success() => {
+                eprintln!("Webhook {} responded with {}", url, response.status())
+            }
+            Err(e) => eprintln!("Failed to send webhook {}: {}", url, e),
+            _ => {}
+        }
+    })
+}
+
+fn append_log(path: &Path, event: &AlertEvent) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn write_syslog(event: &AlertEvent) -> Result<(), String> {
+    use std::ffi::CString;
+
+    let priority = match event.severity {
+        AlertSeverity::Info => libc::LOG_INFO,
+        AlertSeverity::Warning => libc::LOG_WARNING,
+        AlertSeverity::Critical => libc::LOG_CRIT,
+    };
+    let message = CString::new(format!("{}: {}", event.rule_name, event.message))
+        .map_err(|e| e.to_string())?;
+
+    // The ident must outlive the connection, so it is a static C string
+    static OPEN: std::sync::Once = std::sync::Once::new();
+    OPEN.call_once(|| unsafe {
+        libc::openlog(c"actiowatch".as_ptr(), libc::LOG_PID, libc::LOG_USER);
+    });
+    unsafe {
+        libc::syslog(priority, c"%s".as_ptr(), message.as_ptr());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_syslog(_event: &AlertEvent) -> Result<(), String> {
+    Err("Syslog is not supported on this OS".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn event() -> AlertEvent {
+        AlertEvent {
+            rule_id: "rule-1".to_string(),
+            rule_name: "CPU {message}".to_string(),
+            severity: AlertSeverity::Critical,
+            timestamp: 1_700_000_000_000,
+            subject: "worker (250)".to_string(),
+            pid: Some(250),
+            process_name: Some("{rule}".to_string()),
+            value: 95.0,
+            threshold: 90.0,
+            message: "Process {name} is busy".to_string(),
+        }
+    }
+
+    #[test]
+    fn expands_placeholders_once() {
+        let expanded = expand_template(
+            "{pid} {name} {rule} {message} {value}/{threshold}",
+            &event(),
+        );
+        assert_eq!(
+            expanded,
+            "250 {rule} CPU {message} Process {name} is busy 95.00/90.00"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_and_unclosed_braces() {
+        let expanded = expand_template("{{pid}} {unknown} {severity} {", &event());
+        assert_eq!(expanded, "{250} {unknown} critical {");
+    }
+
+    /// Read one HTTP request and answer 200, returning its headers and body. Panics
+    /// if the client hangs up or stalls before the whole request has arrived.
+    fn serve_once(listener: TcpListener) -> (String, String) {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buffer).unwrap();
+            assert!(
+                read > 0,
+                "connection closed mid-request: {}",
+                String::from_utf8_lossy(&request)
+            );
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                        .unwrap();
+                    let head = text[..end].to_string();
+                    return (head, text[end + 4..].to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn webhook_posts_the_event_as_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || serve_once(listener));
+
+        let headers = [("X-Token".to_string(), "secret".to_string())].into();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async { send_webhook(url, headers, event()).await })
+            .unwrap();
+
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /hook HTTP/1.1"));
+        assert!(head.to_lowercase().contains("x-token: secret"));
+        let received: AlertEvent = serde_json::from_str(&body).unwrap();
+        assert_eq!(received.rule_id, "rule-1");
+        assert_eq!(received.pid, Some(250));
+        assert_eq!(received.message, "Process {name} is busy");
+    }
+}
@@ -0,0 +1,501 @@
+pub mod actions;
+
+use crate::history::now_ms;
+use crate::models::{
+    AlertAction, AlertComparison, AlertEvent, AlertMetric, AlertRule, AlertSeverity, ProcessInfo,
+    SystemStats,
+};
+use crate::query::Query;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Number of fired alerts kept for the history list
+const HISTORY_LIMIT: usize = 500;
+
+/// User-defined alert rules (persisted as JSON) and the list of recently fired alerts.
+pub struct AlertStore {
+    path: PathBuf,
+    rules: Mutex<Vec<AlertRule>>,
+    history: Mutex<VecDeque<AlertEvent>>,
+}
+
+/// Rules matching the previous hard-coded watchdog, used until the user saves their own
+pub fn default_rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule {
+            id: "default-high-cpu".to_string(),
+            name: "High CPU Alert".to_string(),
+            enabled: true,
+            metric: AlertMetric::GlobalCpu,
+            comparison: AlertComparison::Above,
+            threshold: 90.0,
+            duration_secs: 5,
+            hysteresis: 5.0,
+            cooldown_secs: 60,
+            severity: AlertSeverity::Critical,
+            actions: vec![AlertAction::Notify],
+        },
+        AlertRule {
+            id: "default-high-memory".to_string(),
+            name: "Memory Alert".to_string(),
+            enabled: true,
+            metric: AlertMetric::MemoryPercent,
+            comparison: AlertComparison::Above,
+            threshold: 90.0,
+            duration_secs: 3,
+            hysteresis: 5.0,
+            cooldown_secs: 60,
+            severity: AlertSeverity::Warning,
+            actions: vec![AlertAction::Notify],
+        },
+        AlertRule {
+            id: "default-process-memory".to_string(),
+            name: "High Memory Usage".to_string(),
+            enabled: true,
+            metric: AlertMetric::ProcessMemoryPercent {
+                name: None,
+                query: None,
+            },
+            comparison: AlertComparison::Above,
+            threshold: 25.0,
+            duration_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 60,
+            severity: AlertSeverity::Warning,
+            actions: vec![AlertAction::Notify],
+        },
+    ]
+}
+
+impl AlertStore {
+    /// Load rules from `path`, falling back to the default rules when the file doesn't exist yet
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let rules = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => default_rules(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path,
+            rules: Mutex::new(rules),
+            history: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn rules(&self) -> Vec<AlertRule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    /// Insert a new rule or replace the one with the same id
+    pub fn save_rule(&self, rule: AlertRule) -> io::Result<()> {
+        let mut rules = self.rules.lock().unwrap();
+        match rules.iter_mut().find(|r| r.id == rule.id) {
+            Some(existing) => *existing = rule,
+            None => rules.push(rule),
+        }
+        self.persist(&rules)
+    }
+
+    /// Returns false if no rule with that id exists
+    pub fn delete_rule(&self, id: &str) -> io::Result<bool> {
+        let mut rules = self.rules.lock().unwrap();
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        if rules.len() == before {
+            return Ok(false);
+        }
+        self.persist(&rules).map(|_| true)
+    }
+
+    pub fn push_event(&self, event: AlertEvent) {
+        let mut history = self.history.lock().unwrap();
+        if history.len() >= HISTORY_LIMIT {
+            history.pop_front();
+        }
+        history.push_back(event);
+    }
+
+    /// Fired alerts, most recent first
+    pub fn history(&self) -> Vec<AlertEvent> {
+        self.history.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn clear_history(&self) {
+        self.history.lock().unwrap().clear();
+    }
+
+    fn persist(&self, rules: &[AlertRule]) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(rules)?)
+    }
+}
+
+/// Tracking state of one rule for one subject (the system, a core, a process, ...)
+struct RuleState {
+    breach_since: Option<Instant>,
+    active: bool,
+    last_fired: Option<Instant>,
+}
+
+/// Value of a rule's metric for one subject
+struct Reading {
+    key: String,
+    subject: String,
+    pid: Option<u32>,
+    process_name: Option<String>,
+    value: f64,
+}
+
+/// Evaluates rules against consecutive samples, keeping per-subject duration,
+/// hysteresis and cooldown state between ticks.
+#[derive(Default)]
+pub struct AlertEvaluator {
+    states: HashMap<(String, String), RuleState>,
+}
+
+impl AlertEvaluator {
+    /// Evaluate enabled rules against the latest sample. `listening_ports` is only
+    /// needed when a `PortListening` rule is enabled (see `needs_ports`).
+    pub fn evaluate(
+        &mut self,
+        rules: &[AlertRule],
+        stats: &SystemStats,
+        listening_ports: Option<&HashSet<u16>>,
+    ) -> Vec<AlertEvent> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        let mut seen = HashSet::new();
+
+        let mut processes = Vec::new();
+        flatten_processes(&stats.top_processes, &mut processes);
+
+        for rule in rules.iter().filter(|r| r.enabled) {
+            for reading in readings(rule, stats, &processes, listening_ports) {
+                let key = (rule.id.clone(), reading.key.clone());
+                seen.insert(key.clone());
+
+                let state = self.states.entry(key).or_insert(RuleState {
+                    breach_since: None,
+                    active: false,
+                    last_fired: None,
+                });
+
+                if state.active {
+                    // Stay active until the value moves back past the threshold by the hysteresis margin
+                    let cleared = match rule.comparison {
+                        AlertComparison::Above => reading.value < rule.threshold - rule.hysteresis,
+                        AlertComparison::Below => reading.value > rule.threshold + rule.hysteresis,
+                    };
+                    if cleared {
+                        state.active = false;
+                        state.breach_since = None;
+                    }
+                    continue;
+                }
+
+                let breaching = match rule.comparison {
+                    AlertComparison::Above => reading.value > rule.threshold,
+                    AlertComparison::Below => reading.value < rule.threshold,
+                };
+                if !breaching {
+                    state.breach_since = None;
+                    continue;
+                }
+
+                let since = *state.breach_since.get_or_insert(now);
+                if now.duration_since(since) < Duration::from_secs(rule.duration_secs) {
+                    continue;
+                }
+
+                state.active = true;
+                let cooled_down = state.last_fired.is_none_or(|last| {
+                    now.duration_since(last) >= Duration::from_secs(rule.cooldown_secs)
+                });
+                if cooled_down {
+                    state.last_fired = Some(now);
+                    events.push(AlertEvent {
+                        rule_id: rule.id.clone(),
+                        rule_name: rule.name.clone(),
+                        severity: rule.severity,
+                        timestamp: now_ms(),
+                        message: describe(rule, &reading),
+                        subject: reading.subject,
+                        pid: reading.pid,
+                        process_name: reading.process_name,
+                        value: reading.value,
+                        threshold: rule.threshold,
+                    });
+                }
+            }
+        }
+
+        // Forget subjects that disappeared (exited processes, removed rules, unplugged sensors)
+        self.states.retain(|key, _| seen.contains(key));
+
+        events
+    }
+}
+
+/// Whether any enabled rule needs the list of listening ports
+pub fn needs_ports(rules: &[AlertRule]) -> bool {
+    rules
+        .iter()
+        .any(|r| r.enabled && matches!(r.metric, AlertMetric::PortListening { .. }))
+}
+
+fn flatten_processes<'a>(nodes: &'a [ProcessInfo], out: &mut Vec<&'a ProcessInfo>) {
+    for node in nodes {
+        out.push(node);
+        flatten_processes(&node.children, out);
+    }
+}
+
+fn system_reading(value: f64) -> Vec<Reading> {
+    vec![Reading {
+        key: "system".to_string(),
+        subject: "System".to_string(),
+        pid: None,
+        process_name: None,
+        value,
+    }]
+}
+
+fn readings(
+    rule: &AlertRule,
+    stats: &SystemStats,
+    processes: &[&ProcessInfo],
+    listening_ports: Option<&HashSet<u16>>,
+) -> Vec<Reading> {
+    let memory_percent = |used: u64| {
+        if stats.memory_total == 0 {
+            0.0
+        } else {
+            used as f64 / stats.memory_total as f64 * 100.0
+        }
+    };
+
+    let process_readings =
+        |name: &Option<String>, query: &Option<String>, value: &dyn Fn(&ProcessInfo) -> f64| {
+            // Queries are checked when the rule is saved; one edited into the file by hand
+            // and broken matches nothing rather than everything
+            let query = match query.as_deref().map(Query::parse) {
+                Some(Ok(query)) => Some(query),
+                Some(Err(_)) => return Vec::new(),
+                None => None,
+            };
+            processes
+                .iter()
+                .filter(|p| name.as_ref().is_none_or(|n| p.name.eq_ignore_ascii_case(n)))
+                .filter(|p| query.as_ref().is_none_or(|q| q.matches(**p)))
+                .map(|p| Reading {
+                    // With the start time, so a reused PID starts from a clean state
+                    key: format!("{}@{}", p.pid, p.start_ticks.unwrap_or(p.start_time)),
+                    subject: format!("{} ({})", p.name, p.pid),
+                    pid: Some(p.pid),
+                    process_name: Some(p.name.clone()),
+                    value: value(p),
+                })
+                .collect()
+        };
+
+    match &rule.metric {
+        AlertMetric::GlobalCpu => system_reading(stats.cpu_usage as f64),
+        AlertMetric::CoreCpu { core } => match stats.cpu_cores.get(*core) {
+            Some(usage) => vec![Reading {
+                key: format!("core-{}", core),
+                subject: format!("CPU {}", core),
+                pid: None,
+                process_name: None,
+                value: *usage as f64,
+            }],
+            None => Vec::new(),
+        },
+        AlertMetric::MemoryPercent => system_reading(memory_percent(stats.memory_used)),
+        AlertMetric::MemoryUsed => system_reading(stats.memory_used as f64),
+        AlertMetric::NetworkUp => system_reading(stats.network_up as f64),
+        AlertMetric::NetworkDown => system_reading(stats.network_down as f64),
+        AlertMetric::DiskRead => system_reading(stats.disk_read as f64),
+        AlertMetric::DiskWrite => system_reading(stats.disk_write as f64),
+        AlertMetric::ProcessCpu { name, query } => {
+            process_readings(name, query, &|p| p.cpu_usage as f64)
+        }
+        AlertMetric::ProcessMemory { name, query } => {
+            process_readings(name, query, &|p| p.memory_usage as f64)
+        }
+        AlertMetric::ProcessMemoryPercent { name, query } => {
+            process_readings(name, query, &|p| memory_percent(p.memory_usage))
+        }
+        AlertMetric::SensorTemperature { label } => stats
+            .components
+            .iter()
+            .filter(|c| c.label == *label)
+            .map(|c| Reading {
+                key: c.label.clone(),
+                subject: c.label.clone(),
+                pid: None,
+                process_name: None,
+                value: c.temperature as f64,
+            })
+            .collect(),
+        AlertMetric::PortListening { port } => match listening_ports {
+            Some(ports) => vec![Reading {
+                key: format!("port-{}", port),
+                subject: format!("Port {}", port),
+                pid: None,
+                process_name: None,
+                value: if ports.contains(port) { 1.0 } else { 0.0 },
+            }],
+            None => Vec::new(),
+        },
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1_073_741_824.0 {
+        format!("{:.2} GB", bytes / 1_073_741_824.0)
+    } else {
+        format!("{:.1} MB", bytes / 1_048_576.0)
+    }
+}
+
+/// Human readable notification body for a fired rule
+fn describe(rule: &AlertRule, reading: &Reading) -> String {
+    let direction = match rule.comparison {
+        AlertComparison::Above => "above",
+        AlertComparison::Below => "below",
+    };
+
+    match &rule.metric {
+        AlertMetric::GlobalCpu | AlertMetric::CoreCpu { .. } => format!(
+            "{} usage is {:.1}% ({} {:.0}%)",
+            reading.subject, reading.value, direction, rule.threshold
+        ),
+        AlertMetric::MemoryPercent => format!(
+            "System memory usage is {:.1}% ({} {:.0}%)",
+            reading.value, direction, rule.threshold
+        ),
+        AlertMetric::MemoryUsed => format!(
+            "System memory usage is {} ({} {})",
+            format_bytes(reading.value),
+            direction,
+            format_bytes(rule.threshold)
+        ),
+        AlertMetric::NetworkUp | AlertMetric::NetworkDown => format!(
+            "Network {} is {}/s ({} {}/s)",
+            if matches!(rule.metric, AlertMetric::NetworkUp) {
+                "upload"
+            } else {
+                "download"
+            },
+            format_bytes(reading.value),
+            direction,
+            format_bytes(rule.threshold)
+        ),
+        AlertMetric::DiskRead | AlertMetric::DiskWrite => format!(
+            "Disk {} is {}/s ({} {}/s)",
+            if matches!(rule.metric, AlertMetric::DiskRead) {
+                "read"
+            } else {
+                "write"
+            },
+            format_bytes(reading.value),
+            direction,
+            format_bytes(rule.threshold)
+        ),
+        AlertMetric::ProcessCpu { .. } => format!(
+            "Process {} is using {:.1}% CPU",
+            reading.subject, reading.value
+        ),
+        AlertMetric::ProcessMemory { .. } => format!(
+            "Process {} is using {} RAM",
+            reading.subject,
+            format_bytes(reading.value)
+        ),
+        AlertMetric::ProcessMemoryPercent { .. } => format!(
+            "Process {} is using {:.1}% of system RAM",
+            reading.subject, reading.value
+        ),
+        AlertMetric::SensorTemperature { .. } => format!(
+            "{} temperature is {:.1}°C ({} {:.0}°C)",
+            reading.subject, reading.value, direction, rule.threshold
+        ),
+        AlertMetric::PortListening { port } => {
+            if reading.value > 0.0 {
+                format!("Port {} is now listening", port)
+            } else {
+                format!("Nothing is listening on port {}", port)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, start_ticks: u64, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: "worker".into(),
+            start_time: 0,
+            start_ticks: Some(start_ticks),
+            user: String::new(),
+            exe: String::new(),
+            cpu_usage,
+            total_cpu_usage: cpu_usage,
+            memory_usage: 0,
+            total_memory_usage: 0,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            total_disk_read: 0,
+            total_disk_write: 0,
+            thread_count: 1,
+            suspended: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reused_pid_is_a_new_subject() {
+        let rule = AlertRule {
+            id: "busy".into(),
+            name: "Busy process".into(),
+            enabled: true,
+            metric: AlertMetric::ProcessCpu {
+                name: None,
+                query: None,
+            },
+            comparison: AlertComparison::Above,
+            threshold: 50.0,
+            duration_secs: 0,
+            hysteresis: 5.0,
+            cooldown_secs: 0,
+            severity: AlertSeverity::Warning,
+            actions: Vec::new(),
+        };
+        let sample = |p| SystemStats {
+            top_processes: vec![p],
+            ..Default::default()
+        };
+        let mut evaluator = AlertEvaluator::default();
+        let mut fired = |p| {
+            evaluator
+                .evaluate(std::slice::from_ref(&rule), &sample(p), None)
+                .len()
+        };
+
+        assert_eq!(fired(process(7, 100, 90.0)), 1);
+        // Still the same process, still active
+        assert_eq!(fired(process(7, 100, 90.0)), 0);
+        // Another process took the PID
+        assert_eq!(fired(process(7, 200, 90.0)), 1);
+    }
+}
@@ -0,0 +1,822 @@
+use crate::commands::memory::memory_map;
+use crate::commands::signal::{self, parse_signal, DEFAULT_GRACE};
+use crate::commands::startup::get_startup_apps;
+use crate::commands::tree;
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::models::{
+    GroupBy, IoClass, ProcessInfo, ProcessSignal, TerminateStage, TreeActionResult,
+};
+use crate::monitor::{set_totals, Monitor};
+use crate::query::Query;
+use crate::source::fixture::{Fixture, SourceFrame};
+use crate::source::{self, LiveSource, SystemSource};
+use serde::Serialize;
+
+const USAGE: &str = "Usage: actiowatch <command> [options]
+
+Commands:
+  tree              Process tree
+  ps                Flat process list
+  groups            Processes grouped by executable, user, cgroup or application
+  ports             Open ports
+  startup           Startup applications
+  details <pid>     Details of one process
+  maps <pid>        Memory mappings of one process, summed per file
+  kill <pid>        Ask a process to exit, force-killing it after a grace period
+  suspend <pid>     Pause a process without ending it
+  resume <pid>      Continue a suspended process
+  signal <pid> <sig> Send a signal: term, kill, hup, int, usr1, usr2, stop or cont
+  tui               Interactive terminal UI
+  record <file>     Record samples to a fixture for ACTIOWATCH_FIXTURE
+  help              Show this help
+
+Options:
+  --json            Print JSON
+  --csv             Print CSV
+  --name <text>     (tree, ps) Only processes whose name contains <text>
+  --query <expr>    (tree, ps) Only processes matching <expr>, e.g. 'cpu>20 mem>500MB user:root'
+  --aggregate       (tree, ps) Show each process plus its descendants in CPU, memory and disk
+  --sort <key>      (ps) cpu, memory, pss, uss, swap, pid or name (default: cpu)
+  --by <key>        (groups) exe, user, cgroup or app (default: exe)
+  --limit <n>       (ps, groups, maps) Print at most <n> rows; (record) samples to take (default: 10)
+  --grace <secs>    (kill) How long to wait before force-killing (default: 5)
+  --force           (kill) Kill immediately, without asking first
+  --tree            (kill, suspend, resume) Also act on all descendants
+  --port <port>     (ports) Only this port
+  --pid <pid>       (ports) Only ports owned by this process
+
+Run without a command to start the desktop app, or with --headless for the daemon.";
+
+const COMMANDS: [&str; 14] = [
+    "tree", "ps", "groups", "ports", "startup", "details", "maps", "kill", "suspend", "resume",
+    "signal", "tui", "record", "help",
+];
+
+/// Whether the first argument selects a CLI command instead of the GUI
+pub fn is_cli_command(arg: &str) -> bool {
+    COMMANDS.contains(&arg) || arg == "--help"
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+struct Options {
+    format: Format,
+    name: Option<String>,
+    query: Option<Query>,
+    aggregate: bool,
+    sort: String,
+    by: String,
+    limit: Option<usize>,
+    grace: std::time::Duration,
+    force: bool,
+    tree: bool,
+    port: Option<u16>,
+    pid: Option<u32>,
+    positional: Vec<String>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        format: Format::Table,
+        name: None,
+        query: None,
+        aggregate: false,
+        sort: "cpu".to_string(),
+        by: "exe".to_string(),
+        limit: None,
+        grace: DEFAULT_GRACE,
+        force: false,
+        tree: false,
+        port: None,
+        pid: None,
+        positional: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+
+        match arg.as_str() {
+            "--json" => options.format = Format::Json,
+            "--csv" => options.format = Format::Csv,
+            "--aggregate" => options.aggregate = true,
+            "--name" => options.name = Some(value("--name")?.to_lowercase()),
+            "--query" => {
+                options.query = Some(Query::parse(&value("--query")?).map_err(|e| e.to_string())?)
+            }
+            "--sort" => options.sort = value("--sort")?,
+            "--by" => options.by = value("--by")?,
+            "--limit" => {
+                let limit = value("--limit")?;
+                options.limit = Some(
+                    limit
+                        .parse()
+                        .map_err(|_| format!("Invalid limit: {}", limit))?,
+                );
+            }
+            "--grace" => {
+                let grace = value("--grace")?;
+                options.grace = grace
+                    .parse()
+                    .ok()
+                    .and_then(|secs| std::time::Duration::try_from_secs_f64(secs).ok())
+                    .ok_or_else(|| format!("Invalid grace period: {}", grace))?;
+            }
+            "--force" => options.force = true,
+            "--tree" => options.tree = true,
+            "--port" => {
+                let port = value("--port")?;
+                options.port = Some(
+                    port.parse()
+                        .map_err(|_| format!("Invalid port: {}", port))?,
+                );
+            }
+            "--pid" => {
+                let pid = value("--pid")?;
+                options.pid = Some(pid.parse().map_err(|_| format!("Invalid PID: {}", pid))?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Run a CLI command (`args[0]` is the command) and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    let command = args[0].as_str();
+    if command == "help" || command == "--help" {
+        println!("{}", USAGE);
+        return 0;
+    }
+    if command == "tui" {
+        return crate::tui::run();
+    }
+
+    let options = match parse_options(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    let result = match command {
+        "tree" => print_tree(&options),
+        "ps" => print_processes(&options),
+        "groups" => print_groups(&options),
+        "ports" => print_ports(&options),
+        "startup" => print_startup(&options),
+        "details" => print_details(&options),
+        "maps" => print_memory_map(&options),
+        "kill" | "suspend" | "resume" | "signal" if source::fixture_from_env().is_some() => {
+            Err(CommandError::new(
+                ErrorKind::Unsupported,
+                "cli",
+                format!("'{}' is disabled while replaying a fixture", command),
+            ))
+        }
+        "kill" => kill(&options),
+        "suspend" => suspend(&options, true),
+        "resume" => suspend(&options, false),
+        "signal" => send_signal(&options),
+        "record" => record_fixture(&options),
+        _ => Err(CommandError::invalid_argument(
+            "cli",
+            format!("Unknown command: {}", command),
+        )),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            if e.kind == ErrorKind::PermissionDenied {
+                eprintln!("Try again with elevated privileges (sudo or an Administrator console).");
+            }
+            1
+        }
+    }
+}
+
+/// Release builds use the Windows GUI subsystem; reattach to the calling console so output is visible
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+/// Sample twice so per-process CPU usage has a previous measurement to compare with
+fn sampled_monitor() -> (Monitor, Vec<ProcessInfo>) {
+    sampled_monitor_with(false)
+}
+
+/// Like `sampled_monitor`, optionally reading per-process memory breakdowns
+fn sampled_monitor_with(memory_breakdown: bool) -> (Monitor, Vec<ProcessInfo>) {
+    let mut monitor = Monitor::new();
+    monitor.sample();
+    monitor.set_memory_breakdown(memory_breakdown);
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    let processes = monitor.sample().top_processes;
+    (monitor, processes)
+}
+
+/// Sampled process tree, with subtree totals if --aggregate was given
+fn sample_processes(options: &Options) -> Vec<ProcessInfo> {
+    let breakdown = matches!(options.sort.as_str(), "pss" | "uss" | "swap");
+    let mut processes = sampled_monitor_with(breakdown).1;
+    set_totals(&mut processes, options.aggregate);
+    processes
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1_073_741_824.0 {
+        format!("{:.2} GB", bytes / 1_073_741_824.0)
+    } else if bytes >= 1_048_576.0 {
+        format!("{:.1} MB", bytes / 1_048_576.0)
+    } else {
+        format!("{:.1} KB", bytes / 1024.0)
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> CommandResult<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| CommandError::new(ErrorKind::Io, "print_json", e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_rows(format: Format, headers: &[&str], rows: &[Vec<String>]) {
+    if format == Format::Csv {
+        println!("{}", headers.join(","));
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+            println!("{}", fields.join(","));
+        }
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let line = |fields: Vec<&str>| {
+        let padded: Vec<String> = fields
+            .iter()
+            .zip(&widths)
+            .map(|(f, w)| format!("{:<width$}", f, width = w))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+
+    line(headers.to_vec());
+    for row in rows {
+        line(row.iter().map(|f| f.as_str()).collect());
+    }
+}
+
+fn process_row(p: &ProcessInfo, format: Format, name: String) -> Vec<String> {
+    let bytes = |b: u64| {
+        if format == Format::Csv {
+            b.to_string()
+        } else {
+            format_bytes(b)
+        }
+    };
+
+    vec![
+        p.pid.to_string(),
+        name,
+        format!("{:.1}", p.total_cpu_usage),
+        bytes(p.total_memory_usage),
+        bytes(p.total_disk_read),
+        bytes(p.total_disk_write),
+        p.thread_count.to_string(),
+    ]
+}
+
+const PROCESS_HEADERS: [&str; 7] = [
+    "PID", "NAME", "CPU%", "MEMORY", "READ/S", "WRITE/S", "THREADS",
+];
+
+/// Whether a process passes the --name and --query filters
+fn matches_filters(options: &Options, p: &ProcessInfo) -> bool {
+    options
+        .name
+        .as_ref()
+        .is_none_or(|name| p.name.to_lowercase().contains(name))
+        && options.query.as_ref().is_none_or(|query| query.matches(p))
+}
+
+/// Keep nodes matching the filters, plus the ancestors needed to reach them
+fn filter_tree(nodes: Vec<ProcessInfo>, options: &Options) -> Vec<ProcessInfo> {
+    nodes
+        .into_iter()
+        .filter_map(|mut node| {
+            node.children = filter_tree(std::mem::take(&mut node.children), options);
+            if matches_filters(options, &node) || !node.children.is_empty() {
+                Some(node)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn print_tree(options: &Options) -> CommandResult<()> {
+    let mut processes = sample_processes(options);
+    if options.name.is_some() || options.query.is_some() {
+        processes = filter_tree(processes, options);
+    }
+
+    if options.format == Format::Json {
+        return print_json(&processes);
+    }
+
+    fn collect(
+        nodes: &[ProcessInfo],
+        depth: usize,
+        format: Format,
+        parent: Option<u32>,
+        rows: &mut Vec<Vec<String>>,
+    ) {
+        for node in nodes {
+            let row = if format == Format::Csv {
+                // Flat CSV keeps the structure in PARENT/DEPTH columns after the PID
+                let mut row = process_row(node, format, node.name.clone());
+                row.insert(1, parent.map(|p| p.to_string()).unwrap_or_default());
+                row.insert(2, depth.to_string());
+                row
+            } else {
+                process_row(node, format, format!("{}{}", "  ".repeat(depth), node.name))
+            };
+            rows.push(row);
+            collect(&node.children, depth + 1, format, Some(node.pid), rows);
+        }
+    }
+
+    let mut rows = Vec::new();
+    collect(&processes, 0, options.format, None, &mut rows);
+
+    if options.format == Format::Csv {
+        let mut headers = vec!["PID", "PARENT", "DEPTH"];
+        headers.extend_from_slice(&PROCESS_HEADERS[1..]);
+        print_rows(options.format, &headers, &rows);
+    } else {
+        print_rows(options.format, &PROCESS_HEADERS, &rows);
+    }
+    Ok(())
+}
+
+fn print_processes(options: &Options) -> CommandResult<()> {
+    fn flatten(nodes: Vec<ProcessInfo>, out: &mut Vec<ProcessInfo>) {
+        for mut node in nodes {
+            let children = std::mem::take(&mut node.children);
+            out.push(node);
+            flatten(children, out);
+        }
+    }
+
+    let mut processes = Vec::new();
+    flatten(sample_processes(options), &mut processes);
+
+    processes.retain(|p| matches_filters(options, p));
+
+    match options.sort.as_str() {
+        "cpu" => processes.sort_by(|a, b| {
+            b.total_cpu_usage
+                .partial_cmp(&a.total_cpu_usage)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        "memory" => processes.sort_by_key(|p| std::cmp::Reverse(p.total_memory_usage)),
+        "pss" => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.memory_breakdown.and_then(|m| m.pss)))
+        }
+        "uss" => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.memory_breakdown.and_then(|m| m.uss)))
+        }
+        "swap" => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.memory_breakdown.and_then(|m| m.swap)))
+        }
+        "pid" => processes.sort_by_key(|p| p.pid),
+        "name" => processes.sort_by_key(|p| p.name.to_lowercase()),
+        other => {
+            return Err(CommandError::invalid_argument(
+                "cli",
+                format!("Unknown sort key: {}", other),
+            ))
+        }
+    }
+
+    if let Some(limit) = options.limit {
+        processes.truncate(limit);
+    }
+
+    if options.format == Format::Json {
+        return print_json(&processes);
+    }
+
+    let rows: Vec<Vec<String>> = processes
+        .iter()
+        .map(|p| process_row(p, options.format, p.name.clone()))
+        .collect();
+    print_rows(options.format, &PROCESS_HEADERS, &rows);
+    Ok(())
+}
+
+fn print_groups(options: &Options) -> CommandResult<()> {
+    let by = match options.by.as_str() {
+        "exe" => GroupBy::Executable,
+        "user" => GroupBy::User,
+        "cgroup" if cfg!(target_os = "linux") => GroupBy::Cgroup,
+        "app" => GroupBy::Application,
+        other => {
+            return Err(CommandError::invalid_argument(
+                "cli",
+                format!("Unknown grouping: {}", other),
+            ))
+        }
+    };
+
+    let mut groups = sampled_monitor().0.process_groups(by);
+    if let Some(limit) = options.limit {
+        groups.truncate(limit);
+    }
+
+    if options.format == Format::Json {
+        return print_json(&groups);
+    }
+
+    let bytes = |b: u64| {
+        if options.format == Format::Csv {
+            b.to_string()
+        } else {
+            format_bytes(b)
+        }
+    };
+    let rows: Vec<Vec<String>> = groups
+        .iter()
+        .map(|g| {
+            vec![
+                g.name.clone(),
+                g.process_count.to_string(),
+                format!("{:.1}", g.cpu_usage),
+                bytes(g.memory_usage),
+                bytes(g.disk_read),
+                bytes(g.disk_write),
+                g.thread_count.to_string(),
+            ]
+        })
+        .collect();
+    print_rows(
+        options.format,
+        &[
+            "GROUP",
+            "PROCESSES",
+            "CPU%",
+            "MEMORY",
+            "READ/S",
+            "WRITE/S",
+            "THREADS",
+        ],
+        &rows,
+    );
+    Ok(())
+}
+
+fn print_ports(options: &Options) -> CommandResult<()> {
+    // Owners only need their names, so skip the full sample a `Monitor` takes
+    let mut ports = match source::fixture_from_env() {
+        Some(fixture) => fixture.ports()?,
+        None => {
+            let mut sys = sysinfo::System::new();
+            sys.refresh_processes_specifics(sysinfo::ProcessRefreshKind::new());
+            source::live::open_ports(&sys)?
+        }
+    };
+    if let Some(port) = options.port {
+        ports.retain(|p| p.port == port);
+    }
+    if let Some(pid) = options.pid {
+        ports.retain(|p| p.pid == Some(pid));
+    }
+
+    if options.format == Format::Json {
+        return print_json(&ports);
+    }
+
+    let rows: Vec<Vec<String>> = ports
+        .iter()
+        .map(|p| {
+            vec![
+                p.port.to_string(),
+                p.protocol.clone(),
+                p.address.clone(),
+                p.pid.map(|pid| pid.to_string()).unwrap_or_default(),
+                p.process_name.clone(),
+            ]
+        })
+        .collect();
+    print_rows(
+        options.format,
+        &["PORT", "PROTOCOL", "ADDRESS", "PID", "PROCESS"],
+        &rows,
+    );
+    Ok(())
+}
+
+fn print_startup(options: &Options) -> CommandResult<()> {
+    let apps = get_startup_apps()?;
+
+    if options.format == Format::Json {
+        return print_json(&apps);
+    }
+
+    let rows: Vec<Vec<String>> = apps
+        .iter()
+        .map(|a| {
+            vec![
+                a.name.clone(),
+                if a.enabled { "yes" } else { "no" }.to_string(),
+                a.path.clone(),
+            ]
+        })
+        .collect();
+    print_rows(options.format, &["NAME", "ENABLED", "COMMAND"], &rows);
+    Ok(())
+}
+
+fn print_details(options: &Options) -> CommandResult<()> {
+    let pid = pid_argument(options, "details")?;
+    let details = sampled_monitor().0.process_details(pid)?;
+
+    if options.format == Format::Json {
+        return print_json(&details);
+    }
+
+    let bytes = |b: u64| {
+        if options.format == Format::Csv {
+            b.to_string()
+        } else {
+            format_bytes(b)
+        }
+    };
+    let breakdown = details.memory_breakdown.unwrap_or_default();
+    let figure = |value: Option<u64>| value.map(bytes).unwrap_or_default();
+
+    let mut rows = vec![
+        vec!["PID".to_string(), details.pid.to_string()],
+        vec!["Name".to_string(), details.name.clone()],
+        vec!["Status".to_string(), details.status.clone()],
+        vec!["User".to_string(), details.user.clone()],
+        vec!["Executable".to_string(), details.exe.clone()],
+        vec!["Command".to_string(), details.cmd.join(" ")],
+        vec!["Working dir".to_string(), details.cwd.clone()],
+        vec!["Root".to_string(), details.root.clone()],
+        vec!["Run time (s)".to_string(), details.run_time.to_string()],
+        vec!["Memory".to_string(), bytes(details.memory_usage)],
+        vec!["USS".to_string(), figure(breakdown.uss)],
+        vec!["PSS".to_string(), figure(breakdown.pss)],
+        vec!["Shared".to_string(), figure(breakdown.shared)],
+        vec!["Swap".to_string(), figure(breakdown.swap)],
+        vec!["CPU%".to_string(), format!("{:.1}", details.cpu_usage)],
+    ];
+    if let Some(scheduling) = details.scheduling {
+        rows.push(vec![
+            "Policy".to_string(),
+            format!("{:?}", scheduling.policy).to_uppercase(),
+        ]);
+        rows.push(vec!["Nice".to_string(), scheduling.nice.to_string()]);
+        rows.push(vec![
+            "RT priority".to_string(),
+            scheduling.rt_priority.to_string(),
+        ]);
+    }
+    if let Some(priority) = details.io_priority {
+        let class = match priority.class {
+            IoClass::None => "none",
+            IoClass::Realtime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        };
+        rows.push(vec![
+            "I/O priority".to_string(),
+            format!("{} {}", class, priority.level),
+        ]);
+    }
+    print_rows(options.format, &["FIELD", "VALUE"], &rows);
+    Ok(())
+}
+
+fn print_memory_map(options: &Options) -> CommandResult<()> {
+    let pid = pid_argument(options, "maps")?;
+    let map = memory_map(pid)?;
+
+    if options.format == Format::Json {
+        return print_json(&map);
+    }
+
+    let bytes = |b: u64| {
+        if options.format == Format::Csv {
+            b.to_string()
+        } else {
+            format_bytes(b)
+        }
+    };
+    let rows: Vec<Vec<String>> = map
+        .summary
+        .iter()
+        .take(options.limit.unwrap_or(usize::MAX))
+        .map(|m| {
+            vec![
+                bytes(m.size),
+                bytes(m.rss),
+                bytes(m.pss),
+                bytes(m.dirty),
+                bytes(m.swap),
+                m.regions.to_string(),
+                m.path.clone(),
+            ]
+        })
+        .collect();
+    print_rows(
+        options.format,
+        &["SIZE", "RSS", "PSS", "DIRTY", "SWAP", "REGIONS", "MAPPING"],
+        &rows,
+    );
+    Ok(())
+}
+
+fn kill(options: &Options) -> CommandResult<()> {
+    let pid = pid_argument(options, "kill")?;
+    if options.tree {
+        let mut monitor = Monitor::new();
+        monitor.sample();
+        let targets = tree::targets(&monitor, "terminate_process_tree", pid, None)?;
+        let results = if options.force {
+            let mut results = tree::act_on_tree(targets.iter().rev(), |pid| {
+                signal::deliver(pid, ProcessSignal::Kill, "kill_process")
+            });
+            results.reverse();
+            results
+        } else {
+            tree::terminate_tree(&targets, options.grace)
+        };
+        return print_tree_results(options.format, &results);
+    }
+    if options.force {
+        signal::deliver(pid, ProcessSignal::Kill, "kill_process")?;
+        println!("Killed process {}", pid);
+        return Ok(());
+    }
+
+    let done = signal::terminate(pid, None, options.grace, |progress| match progress.stage {
+        TerminateStage::Requested => eprintln!("Asked process {} to exit", pid),
+        TerminateStage::Killing => eprintln!("Still running, killing process {}", pid),
+        _ => {}
+    })?;
+    match done.stage {
+        TerminateStage::Killed => println!("Killed process {}", pid),
+        _ => println!("Process {} exited after {} ms", pid, done.elapsed_ms),
+    }
+    Ok(())
+}
+
+/// Suspend or resume a process, or with `--tree` its whole tree
+fn suspend(options: &Options, suspend: bool) -> CommandResult<()> {
+    let (command, operation) = if suspend {
+        ("suspend", "suspend_process")
+    } else {
+        ("resume", "resume_process")
+    };
+    let pid = pid_argument(options, command)?;
+
+    if options.tree {
+        let mut monitor = Monitor::new();
+        monitor.sample();
+        let targets = tree::targets(&monitor, operation, pid, None)?;
+        let results = if suspend {
+            tree::act_on_tree(targets.iter(), |pid| {
+                crate::suspend::suspend(pid, operation)
+            })
+        } else {
+            let mut results = tree::act_on_tree(targets.iter().rev(), |pid| {
+                crate::suspend::resume(pid, operation)
+            });
+            results.reverse();
+            results
+        };
+        return print_tree_results(options.format, &results);
+    }
+
+    if suspend {
+        crate::suspend::suspend(pid, operation)?;
+        println!("Suspended process {}", pid);
+    } else {
+        crate::suspend::resume(pid, operation)?;
+        println!("Resumed process {}", pid);
+    }
+    Ok(())
+}
+
+fn print_tree_results(format: Format, results: &[TreeActionResult]) -> CommandResult<()> {
+    if format == Format::Json {
+        return print_json(&results);
+    }
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|r| {
+            vec![
+                r.pid.to_string(),
+                r.name.clone(),
+                format!("{:?}", r.status).to_lowercase(),
+                r.error.as_ref().map(|e| e.to_string()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_rows(format, &["PID", "NAME", "RESULT", "ERROR"], &rows);
+    Ok(())
+}
+
+fn send_signal(options: &Options) -> CommandResult<()> {
+    let pid = pid_argument(options, "signal")?;
+    let name = options
+        .positional
+        .get(1)
+        .ok_or_else(|| CommandError::invalid_argument("cli", "signal needs a signal name"))?;
+    let sig = parse_signal(name).ok_or_else(|| {
+        CommandError::invalid_argument("cli", format!("Unknown signal: {}", name))
+    })?;
+    signal::deliver(pid, sig, "send_signal")?;
+    println!("Sent {} to process {}", signal::signal_name(sig), pid);
+    Ok(())
+}
+
+/// The PID given as the first argument of `command`
+fn pid_argument(options: &Options, command: &str) -> CommandResult<u32> {
+    options
+        .positional
+        .first()
+        .ok_or_else(|| CommandError::invalid_argument("cli", format!("{} needs a PID", command)))?
+        .parse::<u32>()
+        .map_err(|_| CommandError::invalid_argument("cli", "Invalid PID"))
+}
+
+/// Record live samples one second apart into a fixture that `ACTIOWATCH_FIXTURE` can replay
+fn record_fixture(options: &Options) -> CommandResult<()> {
+    let path = options
+        .positional
+        .first()
+        .ok_or_else(|| CommandError::invalid_argument("cli", "record needs an output file"))?;
+    let samples = options.limit.unwrap_or(10);
+
+    let mut source = LiveSource::new();
+    let mut fixture = Fixture::default();
+    let mut last_refresh = std::time::Instant::now();
+    for i in 0..samples {
+        if i > 0 {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+        source.refresh();
+        let mut frame = SourceFrame::capture(&source);
+        frame.interval_ms = last_refresh.elapsed().as_millis() as u64;
+        last_refresh = std::time::Instant::now();
+        fixture.frames.push(frame);
+        eprintln!("Recorded sample {}/{}", i + 1, samples);
+    }
+
+    let json = serde_json::to_string_pretty(&fixture)
+        .map_err(|e| CommandError::new(ErrorKind::Io, "record", e.to_string()))?;
+    std::fs::write(path, json).map_err(|e| {
+        CommandError::from_io("record", &e).context(&format!("Failed to write {}", path))
+    })
+}
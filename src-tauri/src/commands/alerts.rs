@@ -0,0 +1,100 @@
+use crate::alerts::AlertStore;
+use crate::error::{CommandError, CommandResult};
+use crate::history::now_ms;
+use crate::models::{AlertAction, AlertEvent, AlertMetric, AlertRule};
+use crate::query::Query;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::State;
+
+// Distinguishes rules created within the same millisecond
+static NEXT_RULE: AtomicU64 = AtomicU64::new(0);
+
+#[tauri::command]
+pub fn get_alert_rules(state: State<Arc<AlertStore>>) -> Vec<AlertRule> {
+    state.rules()
+}
+
+/// Create or update a rule. An empty id creates a new rule; the stored rule is returned.
+#[tauri::command]
+pub fn save_alert_rule(
+    state: State<Arc<AlertStore>>,
+    mut rule: AlertRule,
+) -> CommandResult<AlertRule> {
+    let invalid = |message: &str| Err(CommandError::invalid_argument("save_alert_rule", message));
+
+    if rule.name.trim().is_empty() {
+        return invalid("Rule name cannot be empty");
+    }
+    if !rule.threshold.is_finite() {
+        return invalid("Threshold must be a number");
+    }
+    if !rule.hysteresis.is_finite() || rule.hysteresis < 0.0 {
+        return invalid("Hysteresis cannot be negative");
+    }
+    if let AlertMetric::ProcessCpu {
+        query: Some(query), ..
+    }
+    | AlertMetric::ProcessMemory {
+        query: Some(query), ..
+    }
+    | AlertMetric::ProcessMemoryPercent {
+        query: Some(query), ..
+    } = &rule.metric
+    {
+        if let Err(e) = Query::parse(query) {
+            return invalid(&format!("Invalid process query: {}", e));
+        }
+    }
+    for action in &rule.actions {
+        match action {
+            AlertAction::Command { program, .. } if program.trim().is_empty() => {
+                return invalid("Command action needs a program");
+            }
+            AlertAction::Webhook { url, .. }
+                if !(url.starts_with("http://") || url.starts_with("https://")) =>
+            {
+                return invalid("Webhook URL must start with http:// or https://");
+            }
+            AlertAction::LogFile { path } if path.trim().is_empty() => {
+                return invalid("Log file action needs a path");
+            }
+            _ => {}
+        }
+    }
+
+    if rule.id.is_empty() {
+        let serial = NEXT_RULE.fetch_add(1, Ordering::Relaxed);
+        rule.id = format!("rule-{}-{}", now_ms(), serial);
+    }
+
+    state.save_rule(rule.clone()).map_err(|e| {
+        CommandError::from_io("save_alert_rule", &e).context("Failed to save alert rules")
+    })?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn delete_alert_rule(state: State<Arc<AlertStore>>, id: String) -> CommandResult<()> {
+    match state.delete_rule(&id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(CommandError::not_found(
+            "delete_alert_rule",
+            format!("Alert rule {} not found", id),
+        )),
+        Err(e) => {
+            Err(CommandError::from_io("delete_alert_rule", &e)
+                .context("Failed to save alert rules"))
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_alert_history(state: State<Arc<AlertStore>>) -> Vec<AlertEvent> {
+    state.history()
+}
+
+#[tauri::command]
+pub fn clear_alert_history(state: State<Arc<AlertStore>>) {
+    state.clear_history();
+}
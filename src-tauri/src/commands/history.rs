@@ -0,0 +1,42 @@
+use crate::error::{CommandError, CommandResult};
+use crate::history::{HistoryStore, Resolution};
+use crate::models::{HistoryRange, HistorySample};
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_history(
+    state: State<Arc<HistoryStore>>,
+    start: u64,
+    end: u64,
+    resolution: Option<String>,
+) -> CommandResult<Vec<HistorySample>> {
+    if start > end {
+        return Err(CommandError::invalid_argument(
+            "get_history",
+            "Start of range must be before its end",
+        ));
+    }
+
+    // "auto" (or no value) lets the store pick the tier
+    let resolution = match resolution.as_deref() {
+        None | Some("auto") => None,
+        Some(value) => Some(Resolution::parse(value).ok_or_else(|| {
+            CommandError::invalid_argument("get_history", format!("Invalid resolution: {}", value))
+        })?),
+    };
+
+    state
+        .query(start, end, resolution)
+        .map_err(|e| CommandError::from_io("get_history", &e).context("Failed to read history"))
+}
+
+#[tauri::command]
+pub fn get_history_range(state: State<Arc<HistoryStore>>) -> CommandResult<Option<HistoryRange>> {
+    state
+        .bounds()
+        .map(|bounds| bounds.map(|(start, end)| HistoryRange { start, end }))
+        .map_err(|e| {
+            CommandError::from_io("get_history_range", &e).context("Failed to read history")
+        })
+}
@@ -0,0 +1,256 @@
+//! I/O priority, so a build or backup hammering the disk can be made to wait for
+//! interactive processes. Linux has `ioprio_set` classes; Windows only I/O
+//! priority hints (see `IoPriority` for how they map).
+
+use crate::error::{CommandError, CommandResult};
+use crate::identity;
+use crate::models::{IoClass, IoPriority};
+
+#[tauri::command]
+pub fn get_process_io_priority(pid: u32, start_ticks: Option<u64>) -> CommandResult<IoPriority> {
+    identity::verify("get_io_priority", pid, start_ticks)?;
+    read(pid)
+}
+
+/// The Realtime class (and High on Windows) needs administrator rights; on Linux it
+/// is asked for through pkexec
+#[tauri::command]
+pub fn set_process_io_priority(
+    pid: u32,
+    start_ticks: Option<u64>,
+    priority: IoPriority,
+) -> CommandResult<bool> {
+    identity::verify("set_io_priority", pid, start_ticks)?;
+    if priority.level > 7 {
+        return Err(CommandError::invalid_argument(
+            "set_io_priority",
+            "I/O priority level must be between 0 and 7",
+        ));
+    }
+    apply(pid, priority).map(|_| true)
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::read;
+
+#[cfg(target_os = "linux")]
+use linux::apply;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::commands::process::pkexec;
+    use crate::commands::scheduling::threads;
+    use std::io;
+
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: u32 = 13;
+    const IOPRIO_LEVEL_MASK: u32 = 0x7;
+
+    /// The I/O priority of the process's main thread. Without a class of its own it
+    /// is derived from the nice value, as the kernel does.
+    pub fn read(pid: u32) -> CommandResult<IoPriority> {
+        let ret =
+            unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_int) };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            return Err(CommandError::from_io("get_io_priority", &err)
+                .context("Failed to read I/O priority"));
+        }
+
+        let value = ret as u32;
+        let class = match value >> IOPRIO_CLASS_SHIFT {
+            1 => IoClass::Realtime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        let level = match class {
+            IoClass::None => {
+                let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid) };
+                ((nice.clamp(-20, 19) + 20) / 5) as u8
+            }
+            IoClass::Idle => 0,
+            _ => (value & IOPRIO_LEVEL_MASK) as u8,
+        };
+        Ok(IoPriority { class, level })
+    }
+
+    pub fn apply(pid: u32, priority: IoPriority) -> CommandResult<()> {
+        match set_threads(pid, priority) {
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                // `ionice -p` sets each ID it is given, so pass every thread at once
+                let class = class_number(priority.class).to_string();
+                let level = priority.level.to_string();
+                let tids: Vec<String> = threads(pid)
+                    .unwrap_or_else(|_| vec![pid])
+                    .iter()
+                    .map(u32::to_string)
+                    .collect();
+                let mut args = vec!["ionice", "-c", &class];
+                if matches!(priority.class, IoClass::Realtime | IoClass::BestEffort) {
+                    args.extend(["-n", &level]);
+                }
+                args.push("-p");
+                args.extend(tids.iter().map(String::as_str));
+                pkexec("set_io_priority", &args, &err, "Failed to set I/O priority")
+            }
+            result => result.map_err(|e| {
+                CommandError::from_io("set_io_priority", &e).context("Failed to set I/O priority")
+            }),
+        }
+    }
+
+    fn class_number(class: IoClass) -> u32 {
+        match class {
+            IoClass::None => 0,
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        }
+    }
+
+    /// `ioprio_set` on every thread, since each has its own I/O context; threads
+    /// exiting meanwhile are skipped
+    fn set_threads(pid: u32, priority: IoPriority) -> io::Result<()> {
+        let level = match priority.class {
+            IoClass::Realtime | IoClass::BestEffort => priority.level as u32,
+            IoClass::None | IoClass::Idle => 0,
+        };
+        let value = class_number(priority.class) << IOPRIO_CLASS_SHIFT | level;
+
+        for tid in threads(pid)? {
+            let ret = unsafe {
+                libc::syscall(
+                    libc::SYS_ioprio_set,
+                    IOPRIO_WHO_PROCESS,
+                    tid as libc::c_int,
+                    value as libc::c_int,
+                )
+            };
+            if ret != 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::ESRCH) {
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub use windows_hints::read;
+
+#[cfg(target_os = "windows")]
+use windows_hints::apply;
+
+#[cfg(target_os = "windows")]
+mod windows_hints {
+    use super::*;
+    use crate::commands::process::open_process;
+    use crate::error::ErrorKind;
+    use std::ffi::c_void;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, NTSTATUS};
+    use windows::Win32::System::Threading::{PROCESS_QUERY_INFORMATION, PROCESS_SET_INFORMATION};
+
+    windows::core::link!("ntdll.dll" "system" fn NtQueryInformationProcess(process: HANDLE, class: u32, information: *mut c_void, length: u32, returned: *mut u32) -> NTSTATUS);
+    windows::core::link!("ntdll.dll" "system" fn NtSetInformationProcess(process: HANDLE, class: u32, information: *const c_void, length: u32) -> NTSTATUS);
+
+    // PROCESSINFOCLASS::ProcessIoPriority, an IO_PRIORITY_HINT
+    const PROCESS_IO_PRIORITY: u32 = 33;
+    const IO_PRIORITY_VERY_LOW: u32 = 0;
+    const IO_PRIORITY_LOW: u32 = 1;
+    const IO_PRIORITY_NORMAL: u32 = 2;
+    const IO_PRIORITY_HIGH: u32 = 3;
+
+    pub fn read(pid: u32) -> CommandResult<IoPriority> {
+        let handle = open_process(pid, PROCESS_QUERY_INFORMATION, "get_io_priority")?;
+        let mut hint: u32 = IO_PRIORITY_NORMAL;
+        let status = unsafe {
+            let status = NtQueryInformationProcess(
+                handle,
+                PROCESS_IO_PRIORITY,
+                &mut hint as *mut u32 as *mut c_void,
+                std::mem::size_of::<u32>() as u32,
+                std::ptr::null_mut(),
+            );
+            let _ = CloseHandle(handle);
+            status
+        };
+        check(status, "get_io_priority", pid)?;
+
+        Ok(match hint {
+            IO_PRIORITY_VERY_LOW => IoPriority {
+                class: IoClass::Idle,
+                level: 0,
+            },
+            IO_PRIORITY_LOW => IoPriority {
+                class: IoClass::BestEffort,
+                level: 7,
+            },
+            IO_PRIORITY_NORMAL => IoPriority {
+                class: IoClass::BestEffort,
+                level: 4,
+            },
+            // High, or Critical (kernel use only)
+            _ => IoPriority {
+                class: IoClass::Realtime,
+                level: 0,
+            },
+        })
+    }
+
+    pub fn apply(pid: u32, priority: IoPriority) -> CommandResult<()> {
+        let hint: u32 = match priority.class {
+            IoClass::Idle => IO_PRIORITY_VERY_LOW,
+            IoClass::BestEffort if priority.level >= 5 => IO_PRIORITY_LOW,
+            IoClass::BestEffort | IoClass::None => IO_PRIORITY_NORMAL,
+            IoClass::Realtime => IO_PRIORITY_HIGH,
+        };
+
+        let handle = open_process(pid, PROCESS_SET_INFORMATION, "set_io_priority")?;
+        let status = unsafe {
+            let status = NtSetInformationProcess(
+                handle,
+                PROCESS_IO_PRIORITY,
+                &hint as *const u32 as *const c_void,
+                std::mem::size_of::<u32>() as u32,
+            );
+            let _ = CloseHandle(handle);
+            status
+        };
+        check(status, "set_io_priority", pid)
+    }
+
+    fn check(status: NTSTATUS, operation: &'static str, pid: u32) -> CommandResult<()> {
+        // NT_SUCCESS: success and informational codes are non-negative
+        if status.0 >= 0 {
+            return Ok(());
+        }
+        // STATUS_PRIVILEGE_NOT_HELD: High needs SeIncreaseBasePriorityPrivilege
+        let kind = if status.0 as u32 == 0xC000_0061 {
+            ErrorKind::PermissionDenied
+        } else {
+            ErrorKind::Io
+        };
+        Err(CommandError::new(
+            kind,
+            operation,
+            format!(
+                "Failed to access the I/O priority of process {}: NTSTATUS {:#010x}",
+                pid, status.0
+            ),
+        ))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn read(_pid: u32) -> CommandResult<IoPriority> {
+    Err(CommandError::unsupported("get_io_priority"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn apply(_pid: u32, _priority: IoPriority) -> CommandResult<()> {
+    Err(CommandError::unsupported("set_io_priority"))
+}
@@ -0,0 +1,309 @@
+use crate::error::{CommandError, CommandResult};
+use crate::identity;
+use crate::models::{MappingSummary, MemoryMap, MemoryRegion};
+use std::collections::HashMap;
+
+/// Every mapped region of a process with its resident, proportional, dirty and
+/// swapped size, plus the same figures summed per mapping
+#[tauri::command]
+pub fn get_process_memory_map(pid: u32, start_ticks: Option<u64>) -> CommandResult<MemoryMap> {
+    identity::verify("get_memory_map", pid, start_ticks)?;
+    memory_map(pid)
+}
+
+#[cfg(target_os = "linux")]
+pub fn memory_map(pid: u32) -> CommandResult<MemoryMap> {
+    let smaps = std::fs::read_to_string(format!("/proc/{}/smaps", pid)).map_err(|e| {
+        CommandError::from_io("get_memory_map", &e).context("Failed to read memory map")
+    })?;
+    let regions = parse_smaps(&smaps);
+    Ok(MemoryMap {
+        pid,
+        summary: summarize(&regions),
+        regions,
+    })
+}
+
+/// Windows has no `smaps`: each loaded module is reported as one region at its
+/// base address, without sizes
+#[cfg(target_os = "windows")]
+pub fn memory_map(pid: u32) -> CommandResult<MemoryMap> {
+    use crate::commands::process::open_process;
+    use std::mem::size_of;
+    use windows::Win32::Foundation::{CloseHandle, HMODULE};
+    use windows::Win32::System::ProcessStatus::{EnumProcessModules, GetModuleFileNameExW};
+    use windows::Win32::System::Threading::{PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+
+    let handle = open_process(
+        pid,
+        PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+        "get_memory_map",
+    )?;
+    let mut regions = Vec::new();
+
+    unsafe {
+        let mut modules = [HMODULE::default(); 1024];
+        let mut needed = 0;
+        let listed = EnumProcessModules(
+            handle,
+            modules.as_mut_ptr(),
+            (modules.len() * size_of::<HMODULE>()) as u32,
+            &mut needed,
+        );
+        if let Err(e) = listed {
+            let _ = CloseHandle(handle);
+            return Err(
+                CommandError::from_win32("get_memory_map", &e).context("Failed to list modules")
+            );
+        }
+
+        let count = (needed as usize / size_of::<HMODULE>()).min(modules.len());
+        for module in &modules[..count] {
+            let mut buffer = [0u16; 1024];
+            let len = GetModuleFileNameExW(Some(handle), Some(*module), &mut buffer);
+            if len == 0 {
+                continue;
+            }
+            regions.push(MemoryRegion {
+                start: module.0 as u64,
+                end: module.0 as u64,
+                perms: String::new(),
+                offset: 0,
+                device: String::new(),
+                inode: 0,
+                path: String::from_utf16_lossy(&buffer[..len as usize]),
+                size: 0,
+                rss: 0,
+                pss: 0,
+                dirty: 0,
+                swap: 0,
+            });
+        }
+        let _ = CloseHandle(handle);
+    }
+
+    Ok(MemoryMap {
+        pid,
+        summary: summarize(&regions),
+        regions,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn memory_map(_pid: u32) -> CommandResult<MemoryMap> {
+    Err(CommandError::unsupported("get_memory_map"))
+}
+
+/// Regions of `/proc/<pid>/smaps`: a `maps` header line per region, followed by
+/// `Key: value kB` lines
+#[cfg(target_os = "linux")]
+fn parse_smaps(smaps: &str) -> Vec<MemoryRegion> {
+    let mut regions: Vec<MemoryRegion> = Vec::new();
+    for line in smaps.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(first) = fields.next() else {
+            continue;
+        };
+
+        if let Some(key) = first.strip_suffix(':') {
+            let (Some(region), Some(kb)) = (
+                regions.last_mut(),
+                fields.next().and_then(|v| v.parse::<u64>().ok()),
+            ) else {
+                continue;
+            };
+            let bytes = kb * 1024;
+            match key {
+                "Size" => region.size = bytes,
+                "Rss" => region.rss = bytes,
+                "Pss" => region.pss = bytes,
+                "Shared_Dirty" | "Private_Dirty" => region.dirty += bytes,
+                "Swap" => region.swap = bytes,
+                _ => {}
+            }
+        } else if let Some(region) = parse_header(line) {
+            regions.push(region);
+        }
+    }
+    regions
+}
+
+/// `address perms offset dev inode [path]`; the path may contain spaces
+#[cfg(target_os = "linux")]
+fn parse_header(line: &str) -> Option<MemoryRegion> {
+    let mut rest = line;
+    let mut next = || {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        rest = &trimmed[end..];
+        Some(&trimmed[..end]).filter(|field| !field.is_empty())
+    };
+    let (start, end) = next()?.split_once('-')?;
+    let perms = next()?.to_string();
+    let offset = next()?;
+    let device = next()?.to_string();
+    let inode = next()?.parse().ok()?;
+    let start = u64::from_str_radix(start, 16).ok()?;
+    let end = u64::from_str_radix(end, 16).ok()?;
+
+    Some(MemoryRegion {
+        start,
+        end,
+        perms,
+        offset: u64::from_str_radix(offset, 16).ok()?,
+        device,
+        inode,
+        path: rest.trim().to_string(),
+        size: end.saturating_sub(start),
+        rss: 0,
+        pss: 0,
+        dirty: 0,
+        swap: 0,
+    })
+}
+
+/// Regions summed per path, largest resident set first
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn summarize(regions: &[MemoryRegion]) -> Vec<MappingSummary> {
+    let mut by_path: HashMap<&str, MappingSummary> = HashMap::new();
+    for region in regions {
+        let path = if region.path.is_empty() {
+            "[anon]"
+        } else {
+            region.path.as_str()
+        };
+        let summary = by_path.entry(path).or_insert_with(|| MappingSummary {
+            path: path.to_string(),
+            regions: 0,
+            size: 0,
+            rss: 0,
+            pss: 0,
+            dirty: 0,
+            swap: 0,
+        });
+        summary.regions += 1;
+        summary.size += region.size;
+        summary.rss += region.rss;
+        summary.pss += region.pss;
+        summary.dirty += region.dirty;
+        summary.swap += region.swap;
+    }
+
+    let mut summary: Vec<MappingSummary> = by_path.into_values().collect();
+    summary.sort_by(|a, b| b.rss.cmp(&a.rss).then_with(|| a.path.cmp(&b.path)));
+    summary
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    const SMAPS: &str = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 fd:01 1234                       /opt/My App/bin/app
+Size:                  8 kB
+Rss:                   8 kB
+Pss:                   4 kB
+Shared_Dirty:          0 kB
+Private_Dirty:         4 kB
+Swap:                  0 kB
+55d0c0a02000-55d0c0a03000 r-xp 00002000 fd:01 1234                       /opt/My App/bin/app
+Size:                  4 kB
+Rss:                   4 kB
+Pss:                   2 kB
+Swap:                  0 kB
+55d0c1000000-55d0c1021000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                 100 kB
+Pss:                 100 kB
+Private_Dirty:       100 kB
+Swap:                 12 kB
+7f0000000000-7f0000004000 rw-p 00000000 00:00 0 
+Size:                 16 kB
+Rss:                   8 kB
+Pss:                   8 kB
+Shared_Dirty:          2 kB
+Private_Dirty:         6 kB
+7f0000010000-7f0000011000 r--s 00000000 00:05 99                         /memfd:buffer (deleted)
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0                          [stack]
+Size:                132 kB
+Rss:                  16 kB
+Pss:                  16 kB
+7ffd000fe000-7ffd00100000 r-xp 00000000 00:00 0                          [vdso]
+Size:                  8 kB
+Rss:                   4 kB
+Pss:                   0 kB
+";
+
+    #[test]
+    fn regions_are_parsed_from_smaps() {
+        let regions = parse_smaps(SMAPS);
+        let paths: Vec<&str> = regions.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/opt/My App/bin/app",
+                "/opt/My App/bin/app",
+                "[heap]",
+                "",
+                "/memfd:buffer (deleted)",
+                "[stack]",
+                "[vdso]",
+            ]
+        );
+
+        let text = &regions[1];
+        assert_eq!(text.start, 0x55d0c0a02000);
+        assert_eq!(text.end, 0x55d0c0a03000);
+        assert_eq!(text.perms, "r-xp");
+        assert_eq!(text.offset, 0x2000);
+        assert_eq!(text.device, "fd:01");
+        assert_eq!(text.inode, 1234);
+        assert_eq!((text.rss, text.pss), (4096, 2048));
+
+        let heap = &regions[2];
+        assert_eq!(heap.size, 132 * 1024);
+        assert_eq!(
+            (heap.rss, heap.dirty, heap.swap),
+            (100 * 1024, 100 * 1024, 12 * 1024)
+        );
+
+        let anon = &regions[3];
+        assert_eq!(anon.dirty, 8 * 1024);
+
+        // No Size/Rss/Pss lines: the size comes from the address range
+        let deleted = &regions[4];
+        assert_eq!(deleted.size, 0x1000);
+        assert_eq!((deleted.rss, deleted.pss, deleted.dirty), (0, 0, 0));
+
+        assert_eq!(regions[6].perms, "r-xp");
+        assert_eq!(regions[6].pss, 0);
+    }
+
+    #[test]
+    fn regions_are_summed_per_mapping() {
+        let summary = summarize(&parse_smaps(SMAPS));
+        let paths: Vec<&str> = summary.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "[heap]",
+                "[stack]",
+                "/opt/My App/bin/app",
+                "[anon]",
+                "[vdso]",
+                "/memfd:buffer (deleted)",
+            ]
+        );
+
+        let app = &summary[2];
+        assert_eq!(app.regions, 2);
+        assert_eq!(app.size, 12 * 1024);
+        assert_eq!(
+            (app.rss, app.pss, app.dirty),
+            (12 * 1024, 6 * 1024, 4 * 1024)
+        );
+
+        let total: u64 = summary.iter().map(|s| s.rss).sum();
+        assert_eq!(total, (8 + 4 + 100 + 8 + 16 + 4) * 1024);
+    }
+}
@@ -1,5 +1,13 @@
+pub mod alerts;
+pub mod history;
+pub mod io_priority;
+pub mod memory;
 pub mod network;
 pub mod process;
+pub mod scheduling;
 pub mod settings;
+pub mod signal;
 pub mod startup;
+pub mod stats;
+pub mod tree;
 pub mod view;
@@ -1,147 +1,101 @@
-use crate::models::ProcessDetails;
-use sysinfo::{Pid, System};
-
-#[tauri::command]
-pub fn kill_process(pid: u32) -> Result<bool, String> {
-    let mut sys = System::new();
-    sys.refresh_processes();
-
-    if let Some(process) = sys.process(Pid::from(pid as usize)) {
-        return Ok(process.kill());
+use super::signal;
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::identity;
+use crate::models::{GroupBy, ProcessDetails, ProcessGroup, ProcessSignal};
+use crate::monitor::SharedMonitor;
+use crate::suspend;
+use tauri::State;
+
+/// Open a process handle, reporting a missing PID (ERROR_INVALID_PARAMETER) as NotFound
+#[cfg(target_os = "windows")]
+pub(crate) fn open_process(
+    pid: u32,
+    access: windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS,
+    operation: &'static str,
+) -> CommandResult<windows::Win32::Foundation::HANDLE> {
+    use windows::Win32::System::Threading::OpenProcess;
+
+    let handle = unsafe { OpenProcess(access, false, pid) }.map_err(|e| {
+        let err = CommandError::from_win32(operation, &e);
+        if err.code == Some(87) {
+            CommandError::process_not_found(operation, pid).with_code(87)
+        } else {
+            err.context("Failed to open process")
+        }
+    })?;
+
+    if handle.is_invalid() {
+        return Err(CommandError::new(
+            ErrorKind::Io,
+            operation,
+            "Invalid process handle",
+        ));
     }
-
-    Err(format!("PID process {} not found", pid))
+    Ok(handle)
 }
 
+// Commands that take a PID also take the `start_ticks` it was listed with, if the
+// caller has them, and refuse to act once the PID belongs to another process
+// (see `identity::verify`).
+
 #[tauri::command]
-pub fn get_process_details(pid: u32) -> Result<ProcessDetails, String> {
-    let mut sys = System::new();
-    sys.refresh_processes();
-
-    if let Some(process) = sys.process(Pid::from(pid as usize)) {
-        Ok(ProcessDetails {
-            pid: process.pid().as_u32(),
-            name: process.name().to_string(),
-            cmd: process.cmd().to_vec(),
-            exe: process
-                .exe()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default(),
-            cwd: process
-                .cwd()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default(),
-            root: process
-                .root()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default(),
-            status: format!("{:?}", process.status()),
-            run_time: process.run_time(),
-            memory_usage: process.memory(),
-            cpu_usage: process.cpu_usage(),
-            environ: process.environ().to_vec(),
-        })
-    } else {
-        Err(format!("Process {} not found", pid))
-    }
+pub fn kill_process(pid: u32, start_ticks: Option<u64>) -> CommandResult<bool> {
+    identity::verify("kill_process", pid, start_ticks)?;
+    signal::deliver(pid, ProcessSignal::Kill, "kill_process").map(|_| true)
 }
 
+/// Pause a process without ending it (see `crate::suspend`)
 #[tauri::command]
-pub fn get_process_modules(pid: u32) -> Result<Vec<crate::models::ModuleInfo>, String> {
-    let mut modules = Vec::new();
+pub fn suspend_process(pid: u32, start_ticks: Option<u64>) -> CommandResult<bool> {
+    identity::verify("suspend_process", pid, start_ticks)?;
+    suspend::suspend(pid, "suspend_process").map(|_| true)
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::mem::size_of;
-        use windows::Win32::Foundation::{CloseHandle, HMODULE};
-        use windows::Win32::System::ProcessStatus::{EnumProcessModules, GetModuleFileNameExW};
-        use windows::Win32::System::Threading::{
-            OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-        };
+#[tauri::command]
+pub fn resume_process(pid: u32, start_ticks: Option<u64>) -> CommandResult<bool> {
+    identity::verify("resume_process", pid, start_ticks)?;
+    suspend::resume(pid, "resume_process").map(|_| true)
+}
 
-        unsafe {
-            let handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)
-                .map_err(|e| format!("Failed to open process: {}", e))?;
-
-            if !handle.is_invalid() {
-                let mut h_modules = [HMODULE::default(); 1024];
-                let mut cb_needed = 0;
-
-                if EnumProcessModules(
-                    handle,
-                    h_modules.as_mut_ptr(),
-                    (h_modules.len() * size_of::<HMODULE>()) as u32,
-                    &mut cb_needed,
-                )
-                .is_ok()
-                {
-                    let module_count = cb_needed as usize / size_of::<HMODULE>();
-                    for i in 0..std::cmp::min(module_count, h_modules.len()) {
-                        let mut buffer = [0u16; 1024];
-                        let len =
-                            GetModuleFileNameExW(Some(handle), Some(h_modules[i]), &mut buffer);
-                        if len > 0 {
-                            let path = String::from_utf16_lossy(&buffer[..len as usize]);
-                            let name = std::path::Path::new(&path)
-                                .file_name()
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or_else(|| "Unknown".to_string());
-
-                            modules.push(crate::models::ModuleInfo { name, path });
-                        }
-                    }
-                }
-                let _ = CloseHandle(handle);
-            }
-        }
-    }
+#[tauri::command]
+pub fn get_process_details(
+    monitor: State<SharedMonitor>,
+    pid: u32,
+    start_ticks: Option<u64>,
+) -> CommandResult<ProcessDetails> {
+    identity::verify("get_process_details", pid, start_ticks)?;
+    monitor.lock().unwrap().process_details(pid)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        use std::io::BufRead;
-        let maps_path = format!("/proc/{}/maps", pid);
-        if let Ok(file) = std::fs::File::open(maps_path) {
-            let reader = std::io::BufReader::new(file);
-            let mut seen_paths = std::collections::HashSet::new();
-
-            for line in reader.lines() {
-                if let Ok(l) = line {
-                    // Line format: address perms offset dev inode pathname
-                    let parts: Vec<&str> = l.split_whitespace().collect();
-                    if parts.len() >= 6 {
-                        let path = parts[5];
-                        if (path.ends_with(".so") || path.contains(".so."))
-                            && !seen_paths.contains(path)
-                        {
-                            seen_paths.insert(path.to_string());
-                            let name = std::path::Path::new(path)
-                                .file_name()
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or_else(|| "Unknown".to_string());
-
-                            modules.push(crate::models::ModuleInfo {
-                                name,
-                                path: path.to_string(),
-                            });
-                        }
-                    }
-                }
-            }
-        }
+/// The processes of the last sample grouped by executable, user, cgroup or application
+#[tauri::command]
+pub fn get_process_groups(
+    monitor: State<SharedMonitor>,
+    by: GroupBy,
+) -> CommandResult<Vec<ProcessGroup>> {
+    if by == GroupBy::Cgroup && !cfg!(target_os = "linux") {
+        return Err(CommandError::unsupported("get_process_groups"));
     }
-
-    Ok(modules)
+    Ok(monitor.lock().unwrap().process_groups(by))
 }
 
 #[tauri::command]
-pub fn set_process_priority(pid: u32, priority: String) -> Result<bool, String> {
+pub fn set_process_priority(
+    pid: u32,
+    start_ticks: Option<u64>,
+    priority: String,
+) -> CommandResult<bool> {
+    identity::verify("set_priority", pid, start_ticks)?;
+    let invalid_priority =
+        || CommandError::invalid_argument("set_priority", "Invalid priority level");
+
     #[cfg(target_os = "windows")]
     {
         use windows::Win32::Foundation::CloseHandle;
         use windows::Win32::System::Threading::{
-            OpenProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS,
-            BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
-            NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION, REALTIME_PRIORITY_CLASS,
+            SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+            HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+            PROCESS_SET_INFORMATION, REALTIME_PRIORITY_CLASS,
         };
 
         let priority_class = match priority.as_str() {
@@ -151,41 +105,64 @@ pub fn set_process_priority(pid: u32, priority: String) -> Result<bool, String>
             "Normal" => NORMAL_PRIORITY_CLASS,
             "Below Normal" => BELOW_NORMAL_PRIORITY_CLASS,
             "Low" => IDLE_PRIORITY_CLASS,
-            _ => return Err("Invalid priority level".to_string()),
+            _ => return Err(invalid_priority()),
         };
 
+        let handle = open_process(pid, PROCESS_SET_INFORMATION, "set_priority")?;
         unsafe {
-            let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
-                .map_err(|e| format!("Failed to open process: {}", e))?;
-
-            if handle.is_invalid() {
-                return Err("Invalid process handle".to_string());
-            }
-
             let result = SetPriorityClass(handle, priority_class);
             let _ = CloseHandle(handle);
 
             match result {
                 Ok(_) => Ok(true),
-                Err(e) => Err(format!("Failed to set priority: {}", e)),
+                Err(e) => {
+                    Err(CommandError::from_win32("set_priority", &e)
+                        .context("Failed to set priority"))
+                }
             }
         }
     }
 
     #[cfg(target_os = "linux")]
     {
-        // Map abstract priority levels to nice values (-20 to 19)
-        // Lower is higher priority
+        use crate::models::{SchedPolicy, Scheduling};
+
+        // Map abstract priority levels to nice values (-20 to 19) under the normal
+        // policy, lower being higher priority. Realtime is the real-time round robin
+        // policy at its lowest priority, which still runs ahead of every normal task.
         let nice_value = match priority.as_str() {
-            "Realtime" => -20, // Requires root
+            "Realtime" => {
+                let scheduling = Scheduling {
+                    policy: SchedPolicy::Rr,
+                    nice: 0,
+                    rt_priority: 1,
+                    deadline: None,
+                };
+                return super::scheduling::apply(pid, &scheduling).map(|_| true);
+            }
             "High" => -10,
             "Above Normal" => -5,
             "Normal" => 0,
             "Below Normal" => 5,
             "Low" => 19,
-            _ => return Err("Invalid priority level".to_string()),
+            _ => return Err(invalid_priority()),
         };
 
+        // A nice value does nothing under a real-time policy; go back to the normal one
+        let current = super::scheduling::read(pid)?;
+        if !matches!(
+            current.policy,
+            SchedPolicy::Other | SchedPolicy::Batch | SchedPolicy::Idle
+        ) {
+            let scheduling = Scheduling {
+                policy: SchedPolicy::Other,
+                nice: nice_value,
+                rt_priority: 0,
+                deadline: None,
+            };
+            return super::scheduling::apply(pid, &scheduling).map(|_| true);
+        }
+
         unsafe {
             // setpriority(which, who, prio)
             // PRIO_PROCESS is 0
@@ -196,30 +173,17 @@ pub fn set_process_priority(pid: u32, priority: String) -> Result<bool, String>
                 let err = std::io::Error::last_os_error();
                 // If permission denied, try pkexec
                 if err.kind() == std::io::ErrorKind::PermissionDenied {
-                    use std::process::Command;
-                    let status = Command::new("pkexec")
-                        .arg("renice")
-                        .arg("-n")
-                        .arg(nice_value.to_string())
-                        .arg("-p")
-                        .arg(pid.to_string())
-                        .status();
-
-                    match status {
-                        Ok(exit_status) => {
-                            if exit_status.success() {
-                                Ok(true)
-                            } else {
-                                Err(format!(
-                                    "Failed to set priority via pkexec: {}",
-                                    exit_status
-                                ))
-                            }
-                        }
-                        Err(e) => Err(format!("Failed to execute pkexec: {}", e)),
-                    }
+                    let args = [
+                        "renice",
+                        "-n",
+                        &nice_value.to_string(),
+                        "-p",
+                        &pid.to_string(),
+                    ];
+                    pkexec("set_priority", &args, &err, "Failed to set priority").map(|_| true)
                 } else {
-                    Err(format!("Failed to set priority: {}", err))
+                    Err(CommandError::from_io("set_priority", &err)
+                        .context("Failed to set priority"))
                 }
             }
         }
@@ -227,39 +191,78 @@ pub fn set_process_priority(pid: u32, priority: String) -> Result<bool, String>
 
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
-        Err("Not supported on this OS".to_string())
+        Err(CommandError::unsupported("set_priority"))
+    }
+}
+
+/// Retry a command that failed with `denied` as root through pkexec, which asks the
+/// user to authenticate
+#[cfg(target_os = "linux")]
+pub(crate) fn pkexec(
+    operation: &'static str,
+    args: &[&str],
+    denied: &std::io::Error,
+    context: &str,
+) -> CommandResult<()> {
+    let status = std::process::Command::new("pkexec").args(args).status();
+
+    // pkexec exits with 126 when the dialog is dismissed and 127 when not authorized
+    match status {
+        Ok(exit_status) => match exit_status.code() {
+            Some(0) => Ok(()),
+            Some(126) => Err(CommandError::new(
+                ErrorKind::ElevationCancelled,
+                operation,
+                "Authentication was cancelled",
+            )),
+            Some(127) => Err(CommandError::from_io(operation, denied)
+                .context(&format!("{} via pkexec", context))),
+            _ => Err(CommandError::new(
+                ErrorKind::Io,
+                operation,
+                format!("{} via pkexec: {}", context, exit_status),
+            )),
+        },
+        // Without pkexec the original permission error is the useful one
+        Err(_) => Err(CommandError::from_io(operation, denied).context(context)),
     }
 }
 
 #[tauri::command]
-pub fn get_process_affinity(pid: u32) -> Result<Vec<u32>, String> {
+pub fn get_process_affinity(
+    monitor: State<SharedMonitor>,
+    pid: u32,
+    start_ticks: Option<u64>,
+) -> CommandResult<Vec<u32>> {
+    identity::verify("get_affinity", pid, start_ticks)?;
+    let cpu_count = monitor.lock().unwrap().cpu_count();
+    process_affinity(pid, cpu_count)
+}
+
+/// CPUs (out of `cpu_count`) the process may run on
+pub fn process_affinity(pid: u32, cpu_count: usize) -> CommandResult<Vec<u32>> {
     #[cfg(target_os = "windows")]
     {
         use windows::Win32::Foundation::CloseHandle;
         use windows::Win32::System::Threading::{
-            GetProcessAffinityMask, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+            GetProcessAffinityMask, PROCESS_QUERY_LIMITED_INFORMATION,
         };
 
+        let handle = open_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, "get_affinity")?;
         unsafe {
-            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
-                .map_err(|e| format!("Failed to open process: {}", e))?;
-
-            if handle.is_invalid() {
-                return Err("Invalid process handle".to_string());
-            }
-
             let mut process_mask = 0;
             let mut system_mask = 0;
 
             let result = GetProcessAffinityMask(handle, &mut process_mask, &mut system_mask);
             let _ = CloseHandle(handle);
 
-            if result.is_err() {
-                return Err("Failed to get affinity mask".to_string());
+            if let Err(e) = result {
+                return Err(CommandError::from_win32("get_affinity", &e)
+                    .context("Failed to get affinity mask"));
             }
 
             let mut cpus = Vec::new();
-            for i in 0..64 {
+            for i in 0..cpu_count.min(64) as u32 {
                 if (process_mask >> i) & 1 == 1 {
                     cpus.push(i);
                 }
@@ -272,52 +275,50 @@ pub fn get_process_affinity(pid: u32) -> Result<Vec<u32>, String> {
     {
         use nix::sched::sched_getaffinity;
         use nix::unistd::Pid;
-        use sysinfo::{CpuRefreshKind, RefreshKind, System};
 
         match sched_getaffinity(Pid::from_raw(pid as i32)) {
             Ok(cpuset) => {
                 let mut cpus = Vec::new();
-                let s = System::new_with_specifics(
-                    RefreshKind::new().with_cpu(CpuRefreshKind::everything()),
-                );
-                for i in 0..s.cpus().len() {
+                for i in 0..cpu_count {
                     if cpuset.is_set(i).unwrap_or(false) {
                         cpus.push(i as u32);
                     }
                 }
                 Ok(cpus)
             }
-            Err(e) => Err(format!("Failed to get affinity: {}", e)),
+            Err(e) => {
+                Err(CommandError::from_errno("get_affinity", e).context("Failed to get affinity"))
+            }
         }
     }
 
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
-        Err("Not supported on this OS".to_string())
+        Err(CommandError::unsupported("get_affinity"))
     }
 }
 
 #[tauri::command]
-pub fn set_process_affinity(pid: u32, cpus: Vec<u32>) -> Result<bool, String> {
+pub fn set_process_affinity(
+    pid: u32,
+    start_ticks: Option<u64>,
+    cpus: Vec<u32>,
+) -> CommandResult<bool> {
+    identity::verify("set_affinity", pid, start_ticks)?;
     if cpus.is_empty() {
-        return Err("At least one CPU must be selected".to_string());
+        return Err(CommandError::invalid_argument(
+            "set_affinity",
+            "At least one CPU must be selected",
+        ));
     }
 
     #[cfg(target_os = "windows")]
     {
         use windows::Win32::Foundation::CloseHandle;
-        use windows::Win32::System::Threading::{
-            OpenProcess, SetProcessAffinityMask, PROCESS_SET_INFORMATION,
-        };
+        use windows::Win32::System::Threading::{SetProcessAffinityMask, PROCESS_SET_INFORMATION};
 
+        let handle = open_process(pid, PROCESS_SET_INFORMATION, "set_affinity")?;
         unsafe {
-            let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
-                .map_err(|e| format!("Failed to open process: {}", e))?;
-
-            if handle.is_invalid() {
-                return Err("Invalid process handle".to_string());
-            }
-
             let mut mask: usize = 0;
             for cpu in cpus {
                 if cpu < 64 {
@@ -330,7 +331,10 @@ pub fn set_process_affinity(pid: u32, cpus: Vec<u32>) -> Result<bool, String> {
 
             match result {
                 Ok(_) => Ok(true),
-                Err(e) => Err(format!("Failed to set affinity: {}", e)),
+                Err(e) => {
+                    Err(CommandError::from_win32("set_affinity", &e)
+                        .context("Failed to set affinity"))
+                }
             }
         }
     }
@@ -344,18 +348,23 @@ pub fn set_process_affinity(pid: u32, cpus: Vec<u32>) -> Result<bool, String> {
         for cpu in cpus {
             // CpuSet size is limited, typically 1024
             if let Err(_) = cpuset.set(cpu as usize) {
-                return Err(format!("Invalid CPU index: {}", cpu));
+                return Err(CommandError::invalid_argument(
+                    "set_affinity",
+                    format!("Invalid CPU index: {}", cpu),
+                ));
             }
         }
 
         match sched_setaffinity(Pid::from_raw(pid as i32), &cpuset) {
             Ok(_) => Ok(true),
-            Err(e) => Err(format!("Failed to set affinity: {}", e)),
+            Err(e) => {
+                Err(CommandError::from_errno("set_affinity", e).context("Failed to set affinity"))
+            }
         }
     }
 
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
-        Err("Not supported on this OS".to_string())
+        Err(CommandError::unsupported("set_affinity"))
     }
 }
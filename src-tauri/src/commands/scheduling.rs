@@ -0,0 +1,263 @@
+//! Linux scheduling policies. A nice value only matters under the fair policies
+//! (`SCHED_OTHER`, `SCHED_BATCH`); real-time ones (`SCHED_FIFO`, `SCHED_RR`) take a
+//! priority from 1 to 99 instead and `SCHED_DEADLINE` a CPU budget.
+
+use crate::error::{CommandError, CommandResult};
+use crate::identity;
+use crate::models::{SchedPolicy, Scheduling};
+
+#[tauri::command]
+pub fn get_process_scheduling(pid: u32, start_ticks: Option<u64>) -> CommandResult<Scheduling> {
+    identity::verify("get_scheduling", pid, start_ticks)?;
+    read(pid)
+}
+
+/// Change the policy of every thread of the process. Raising it above what the user
+/// may set asks for root through pkexec.
+#[tauri::command]
+pub fn set_process_scheduling(
+    pid: u32,
+    start_ticks: Option<u64>,
+    scheduling: Scheduling,
+) -> CommandResult<bool> {
+    identity::verify("set_scheduling", pid, start_ticks)?;
+    let scheduling = validate(scheduling)?;
+    apply(pid, &scheduling).map(|_| true)
+}
+
+/// `scheduling` with the fields its policy ignores reset, or why the kernel would
+/// refuse it
+fn validate(mut scheduling: Scheduling) -> CommandResult<Scheduling> {
+    let invalid = |message: &str| CommandError::invalid_argument("set_scheduling", message);
+
+    if !(-20..=19).contains(&scheduling.nice) {
+        return Err(invalid("Nice value must be between -20 and 19"));
+    }
+    match scheduling.policy {
+        SchedPolicy::Fifo | SchedPolicy::Rr => {
+            if !(1..=99).contains(&scheduling.rt_priority) {
+                return Err(invalid("Real-time priority must be between 1 and 99"));
+            }
+            scheduling.deadline = None;
+        }
+        SchedPolicy::Deadline => {
+            let Some(params) = scheduling.deadline else {
+                return Err(invalid(
+                    "Deadline scheduling needs a runtime, deadline and period",
+                ));
+            };
+            if params.runtime == 0
+                || params.runtime > params.deadline
+                || params.deadline > params.period
+            {
+                return Err(invalid("Runtime, deadline and period must be increasing"));
+            }
+            scheduling.rt_priority = 0;
+        }
+        SchedPolicy::Other | SchedPolicy::Batch | SchedPolicy::Idle => {
+            scheduling.rt_priority = 0;
+            scheduling.deadline = None;
+        }
+    }
+    Ok(scheduling)
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::read;
+
+#[cfg(target_os = "linux")]
+pub(super) use linux::apply;
+
+#[cfg(target_os = "linux")]
+pub(super) use linux::threads;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::commands::process::pkexec;
+    use crate::models::DeadlineParams;
+    use std::io;
+
+    /// `struct sched_attr` of `sched_setattr(2)`
+    #[repr(C)]
+    #[derive(Default)]
+    struct SchedAttr {
+        size: u32,
+        sched_policy: u32,
+        sched_flags: u64,
+        sched_nice: i32,
+        sched_priority: u32,
+        sched_runtime: u64,
+        sched_deadline: u64,
+        sched_period: u64,
+    }
+
+    const POLICIES: [(SchedPolicy, i32); 6] = [
+        (SchedPolicy::Other, libc::SCHED_OTHER),
+        (SchedPolicy::Batch, libc::SCHED_BATCH),
+        (SchedPolicy::Idle, libc::SCHED_IDLE),
+        (SchedPolicy::Fifo, libc::SCHED_FIFO),
+        (SchedPolicy::Rr, libc::SCHED_RR),
+        (SchedPolicy::Deadline, libc::SCHED_DEADLINE),
+    ];
+
+    /// The policy of the process's main thread; glibc has no wrapper for
+    /// `sched_getattr`, and only it reports deadline parameters
+    pub fn read(pid: u32) -> CommandResult<Scheduling> {
+        let mut attr = SchedAttr::default();
+        let size = std::mem::size_of::<SchedAttr>() as u32;
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_sched_getattr,
+                pid as libc::pid_t,
+                &mut attr as *mut SchedAttr,
+                size,
+                0u32,
+            )
+        };
+        if ret != 0 {
+            let err = io::Error::last_os_error();
+            return Err(CommandError::from_io("get_scheduling", &err)
+                .context("Failed to read scheduling policy"));
+        }
+
+        let policy = POLICIES
+            .iter()
+            .find(|&&(_, native)| native as u32 == attr.sched_policy)
+            .map(|&(policy, _)| policy)
+            .ok_or_else(|| {
+                CommandError::new(
+                    crate::error::ErrorKind::Unsupported,
+                    "get_scheduling",
+                    format!("Unknown scheduling policy {}", attr.sched_policy),
+                )
+            })?;
+        Ok(Scheduling {
+            policy,
+            nice: attr.sched_nice,
+            rt_priority: attr.sched_priority,
+            deadline: (policy == SchedPolicy::Deadline).then_some(DeadlineParams {
+                runtime: attr.sched_runtime,
+                deadline: attr.sched_deadline,
+                period: attr.sched_period,
+            }),
+        })
+    }
+
+    pub fn apply(pid: u32, scheduling: &Scheduling) -> CommandResult<()> {
+        match set_threads(pid, scheduling) {
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                let script = elevated_script(pid, scheduling);
+                pkexec(
+                    "set_scheduling",
+                    &["sh", "-c", &script],
+                    &err,
+                    "Failed to set scheduling policy",
+                )
+            }
+            result => result.map_err(|e| {
+                CommandError::from_io("set_scheduling", &e)
+                    .context("Failed to set scheduling policy")
+            }),
+        }
+    }
+
+    /// `sched_setattr` on every thread; threads exiting meanwhile are skipped
+    fn set_threads(pid: u32, scheduling: &Scheduling) -> io::Result<()> {
+        let native = POLICIES
+            .iter()
+            .find(|&&(policy, _)| policy == scheduling.policy)
+            .map_or(libc::SCHED_OTHER, |&(_, native)| native);
+        let deadline = scheduling.deadline;
+        let attr = SchedAttr {
+            size: std::mem::size_of::<SchedAttr>() as u32,
+            sched_policy: native as u32,
+            sched_nice: scheduling.nice,
+            sched_priority: scheduling.rt_priority,
+            sched_runtime: deadline.map_or(0, |d| d.runtime),
+            sched_deadline: deadline.map_or(0, |d| d.deadline),
+            sched_period: deadline.map_or(0, |d| d.period),
+            ..Default::default()
+        };
+
+        for tid in threads(pid)? {
+            let ret = unsafe {
+                libc::syscall(
+                    libc::SYS_sched_setattr,
+                    tid as libc::pid_t,
+                    &attr as *const SchedAttr,
+                    0u32,
+                )
+            };
+            if ret != 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::ESRCH) {
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Thread IDs of the process, which the kernel schedules one by one
+    pub fn threads(pid: u32) -> io::Result<Vec<u32>> {
+        let tids = std::fs::read_dir(format!("/proc/{}/task", pid))?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        Ok(tids)
+    }
+
+    /// `chrt --all-tasks --<policy> [deadline params] --pid <priority> <pid>`
+    fn chrt_args(pid: u32, scheduling: &Scheduling) -> Vec<String> {
+        let flag = match scheduling.policy {
+            SchedPolicy::Other => "--other",
+            SchedPolicy::Batch => "--batch",
+            SchedPolicy::Idle => "--idle",
+            SchedPolicy::Fifo => "--fifo",
+            SchedPolicy::Rr => "--rr",
+            SchedPolicy::Deadline => "--deadline",
+        };
+        let mut args = vec!["chrt".into(), "--all-tasks".into(), flag.into()];
+        if let Some(params) = scheduling.deadline {
+            for (name, value) in [
+                ("--sched-runtime", params.runtime),
+                ("--sched-deadline", params.deadline),
+                ("--sched-period", params.period),
+            ] {
+                args.push(name.into());
+                args.push(value.to_string());
+            }
+        }
+        args.extend([
+            "--pid".into(),
+            scheduling.rt_priority.to_string(),
+            pid.to_string(),
+        ]);
+        args
+    }
+
+    /// What pkexec runs as root, in one shell so it asks only once. chrt leaves the
+    /// nice value alone, so under a fair policy every thread is reniced after it:
+    /// `chrt ... && renice -n <nice> -p <tid>...`. Every word is a flag or a number,
+    /// so nothing needs quoting.
+    fn elevated_script(pid: u32, scheduling: &Scheduling) -> String {
+        let mut script = chrt_args(pid, scheduling).join(" ");
+        if matches!(scheduling.policy, SchedPolicy::Other | SchedPolicy::Batch) {
+            script.push_str(&format!(" && renice -n {} -p", scheduling.nice));
+            for tid in threads(pid).unwrap_or_else(|_| vec![pid]) {
+                script.push_str(&format!(" {}", tid));
+            }
+        }
+        script
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> CommandResult<Scheduling> {
+    Err(CommandError::unsupported("get_scheduling"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn apply(_pid: u32, _scheduling: &Scheduling) -> CommandResult<()> {
+    Err(CommandError::unsupported("set_scheduling"))
+}
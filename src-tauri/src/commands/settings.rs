@@ -1,5 +1,11 @@
-use crate::models::AppLifecycle;
+use crate::config::ConfigStore;
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::exporter::MetricsExporter;
+use crate::models::{
+    AppLifecycle, ExporterConfig, MAX_SAMPLING_INTERVAL_MS, MIN_SAMPLING_INTERVAL_MS,
+};
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tauri::State;
 
 #[tauri::command]
@@ -13,3 +19,53 @@ pub fn set_notifications_enabled(state: State<AppLifecycle>, enabled: bool) {
         .notifications_enabled
         .store(enabled, Ordering::Relaxed);
 }
+
+#[tauri::command]
+pub fn get_exporter_config(config: State<Arc<ConfigStore>>) -> ExporterConfig {
+    config.get().exporter
+}
+
+/// Moving the endpoint waits for the old one to close, so this runs off the main thread
+#[tauri::command]
+pub async fn set_exporter_config(
+    config: State<'_, Arc<ConfigStore>>,
+    exporter: State<'_, Arc<MetricsExporter>>,
+    settings: ExporterConfig,
+) -> CommandResult<()> {
+    // Restart the endpoint first so an unusable address is rejected before it is saved
+    let exporter = exporter.inner().clone();
+    let applied = settings.clone();
+    tauri::async_runtime::spawn_blocking(move || exporter.apply(&applied))
+        .await
+        .map_err(|e| CommandError::new(ErrorKind::Io, "start_exporter", e.to_string()))??;
+    config
+        .update(|c| c.exporter = settings)
+        .map(|_| ())
+        .map_err(|e| CommandError::from_io("save_settings", &e).context("Failed to save settings"))
+}
+
+#[tauri::command]
+pub fn get_sampling_interval(config: State<Arc<ConfigStore>>) -> u64 {
+    config.get().sampling_interval_ms
+}
+
+/// Takes effect on the monitor's next tick
+#[tauri::command]
+pub fn set_sampling_interval(
+    config: State<Arc<ConfigStore>>,
+    interval_ms: u64,
+) -> CommandResult<()> {
+    if !(MIN_SAMPLING_INTERVAL_MS..=MAX_SAMPLING_INTERVAL_MS).contains(&interval_ms) {
+        return Err(CommandError::invalid_argument(
+            "set_sampling_interval",
+            format!(
+                "Sampling interval must be between {} and {} ms",
+                MIN_SAMPLING_INTERVAL_MS, MAX_SAMPLING_INTERVAL_MS
+            ),
+        ));
+    }
+    config
+        .update(|c| c.sampling_interval_ms = interval_ms)
+        .map(|_| ())
+        .map_err(|e| CommandError::from_io("save_settings", &e).context("Failed to save settings"))
+}
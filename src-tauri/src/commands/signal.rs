@@ -0,0 +1,291 @@
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::identity;
+use crate::models::{ProcessSignal, TerminateProgress, TerminateStage};
+use crate::suspend;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// How long `terminate_process` waits for a process to exit before force-killing it
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(5);
+
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// A force-killed process still running after this is stuck (e.g. in uninterruptible I/O)
+pub(super) const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+
+const SIGNALS: [ProcessSignal; 8] = [
+    ProcessSignal::Term,
+    ProcessSignal::Kill,
+    ProcessSignal::Hup,
+    ProcessSignal::Int,
+    ProcessSignal::Usr1,
+    ProcessSignal::Usr2,
+    ProcessSignal::Stop,
+    ProcessSignal::Cont,
+];
+
+pub fn signal_name(signal: ProcessSignal) -> &'static str {
+    match signal {
+        ProcessSignal::Term => "SIGTERM",
+        ProcessSignal::Kill => "SIGKILL",
+        ProcessSignal::Hup => "SIGHUP",
+        ProcessSignal::Int => "SIGINT",
+        ProcessSignal::Usr1 => "SIGUSR1",
+        ProcessSignal::Usr2 => "SIGUSR2",
+        ProcessSignal::Stop => "SIGSTOP",
+        ProcessSignal::Cont => "SIGCONT",
+    }
+}
+
+/// A signal by name, with or without the `SIG` prefix and in any case (`hup`, `SIGHUP`)
+pub fn parse_signal(name: &str) -> Option<ProcessSignal> {
+    let name = name.to_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    SIGNALS
+        .into_iter()
+        .find(|&signal| signal_name(signal)[3..] == *name)
+}
+
+#[tauri::command]
+pub fn send_signal(
+    pid: u32,
+    start_ticks: Option<u64>,
+    signal: ProcessSignal,
+) -> CommandResult<bool> {
+    identity::verify("send_signal", pid, start_ticks)?;
+    deliver(pid, signal, "send_signal").map(|_| true)
+}
+
+/// Ask a process to exit (SIGTERM; a window close or Ctrl+Break on Windows), wait up
+/// to `grace_ms` (default 5 s) for it to do so, then force-kill it. Progress is
+/// emitted as `terminate-progress` events.
+#[tauri::command]
+pub async fn terminate_process(
+    app: AppHandle,
+    pid: u32,
+    start_ticks: Option<u64>,
+    grace_ms: Option<u64>,
+) -> CommandResult<TerminateProgress> {
+    let grace = grace_ms.map(Duration::from_millis).unwrap_or(DEFAULT_GRACE);
+    tauri::async_runtime::spawn_blocking(move || {
+        terminate(pid, start_ticks, grace, |progress| {
+            let _ = app.emit("terminate-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| CommandError::new(ErrorKind::Io, "terminate_process", e.to_string()))?
+}
+
+/// Blocking implementation of `terminate_process`, calling `progress` at every stage
+/// and every `PROGRESS_INTERVAL` while waiting
+pub fn terminate(
+    pid: u32,
+    start_ticks: Option<u64>,
+    grace: Duration,
+    mut progress: impl FnMut(&TerminateProgress),
+) -> CommandResult<TerminateProgress> {
+    const OPERATION: &str = "terminate_process";
+
+    identity::verify(OPERATION, pid, start_ticks)?;
+    let ticks = start_ticks
+        .or_else(|| identity::start_ticks(pid))
+        .ok_or_else(|| CommandError::process_not_found(OPERATION, pid))?;
+
+    let started = Instant::now();
+    let mut report = |stage| {
+        let update = TerminateProgress {
+            pid,
+            stage,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            grace_ms: grace.as_millis() as u64,
+        };
+        progress(&update);
+        update
+    };
+
+    let mut deadline = started + grace;
+    match deliver(pid, ProcessSignal::Term, OPERATION) {
+        Ok(()) => {
+            resume_suspended(pid, ticks, OPERATION);
+            report(TerminateStage::Requested);
+        }
+        // Nothing to ask politely (a Windows process with neither a window nor a console)
+        Err(e) if e.kind == ErrorKind::Unsupported => deadline = started,
+        Err(_) if !identity::is_running(pid, ticks) => return Ok(report(TerminateStage::Exited)),
+        Err(e) => return Err(e),
+    }
+
+    let mut reported = Instant::now();
+    while Instant::now() < deadline {
+        if !identity::is_running(pid, ticks) {
+            return Ok(report(TerminateStage::Exited));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+        if reported.elapsed() >= PROGRESS_INTERVAL {
+            report(TerminateStage::Waiting);
+            reported = Instant::now();
+        }
+    }
+    if !identity::is_running(pid, ticks) {
+        return Ok(report(TerminateStage::Exited));
+    }
+
+    report(TerminateStage::Killing);
+    if let Err(e) = deliver(pid, ProcessSignal::Kill, OPERATION) {
+        if identity::is_running(pid, ticks) {
+            return Err(e);
+        }
+    }
+    let killed = Instant::now();
+    while identity::is_running(pid, ticks) {
+        if killed.elapsed() >= KILL_TIMEOUT {
+            return Err(CommandError::new(
+                ErrorKind::Io,
+                OPERATION,
+                format!("Process {} is still running after being killed", pid),
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    Ok(report(TerminateStage::Killed))
+}
+
+/// A process suspended through `crate::suspend` can't act on a request to exit
+/// until it runs again
+pub(super) fn resume_suspended(pid: u32, ticks: u64, operation: &'static str) {
+    if suspend::is_suspended(pid, Some(ticks)) {
+        let _ = suspend::resume(pid, operation);
+    }
+}
+
+/// Send `signal` to `pid` without checking its identity
+#[cfg(unix)]
+pub fn deliver(pid: u32, signal: ProcessSignal, operation: &'static str) -> CommandResult<()> {
+    use nix::sys::signal::{kill, Signal};
+
+    let native = match signal {
+        ProcessSignal::Term => Signal::SIGTERM,
+        ProcessSignal::Kill => Signal::SIGKILL,
+        ProcessSignal::Hup => Signal::SIGHUP,
+        ProcessSignal::Int => Signal::SIGINT,
+        ProcessSignal::Usr1 => Signal::SIGUSR1,
+        ProcessSignal::Usr2 => Signal::SIGUSR2,
+        ProcessSignal::Stop => Signal::SIGSTOP,
+        ProcessSignal::Cont => Signal::SIGCONT,
+    };
+    kill(nix::unistd::Pid::from_raw(pid as i32), native).map_err(|e| {
+        CommandError::from_errno(operation, e).context(&format!(
+            "Failed to send {} to process {}",
+            signal_name(signal),
+            pid
+        ))
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn deliver(pid: u32, signal: ProcessSignal, operation: &'static str) -> CommandResult<()> {
+    use windows::Win32::System::Console::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+
+    match signal {
+        ProcessSignal::Kill => terminate_now(pid, operation),
+        ProcessSignal::Term if close_windows(pid) => Ok(()),
+        ProcessSignal::Term => console_event(pid, CTRL_BREAK_EVENT, operation),
+        ProcessSignal::Int => console_event(pid, CTRL_C_EVENT, operation),
+        _ => Err(CommandError::new(
+            ErrorKind::Unsupported,
+            operation,
+            format!("{} has no equivalent on Windows", signal_name(signal)),
+        )),
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn deliver(_pid: u32, _signal: ProcessSignal, operation: &'static str) -> CommandResult<()> {
+    Err(CommandError::unsupported(operation))
+}
+
+#[cfg(target_os = "windows")]
+fn terminate_now(pid: u32, operation: &'static str) -> CommandResult<()> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{TerminateProcess, PROCESS_TERMINATE};
+
+    let handle = super::process::open_process(pid, PROCESS_TERMINATE, operation)?;
+    unsafe {
+        let result = TerminateProcess(handle, 1);
+        let _ = CloseHandle(handle);
+
+        result.map_err(|e| {
+            CommandError::from_win32(operation, &e)
+                .context(&format!("Failed to kill process {}", pid))
+        })
+    }
+}
+
+/// Post WM_CLOSE to the visible top-level windows of `pid`; false if it has none
+#[cfg(target_os = "windows")]
+fn close_windows(pid: u32) -> bool {
+    use windows::core::BOOL;
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowThreadProcessId, IsWindowVisible, PostMessageW, WM_CLOSE,
+    };
+
+    struct Search {
+        pid: u32,
+        closed: bool,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let mut owner = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut owner as *mut u32));
+        if owner == search.pid
+            && IsWindowVisible(hwnd).as_bool()
+            && PostMessageW(Some(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0)).is_ok()
+        {
+            search.closed = true;
+        }
+        true.into()
+    }
+
+    let mut search = Search { pid, closed: false };
+    unsafe {
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+    }
+    search.closed
+}
+
+/// Raise a console control event in the console `pid` is attached to. The event goes
+/// to every process sharing that console, as it would from the keyboard.
+#[cfg(target_os = "windows")]
+fn console_event(pid: u32, event: u32, operation: &'static str) -> CommandResult<()> {
+    use windows::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler,
+        ATTACH_PARENT_PROCESS,
+    };
+
+    unsafe {
+        // A process can only be attached to one console, so leave ours for the moment
+        let _ = FreeConsole();
+        if AttachConsole(pid).is_err() {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+            return Err(CommandError::new(
+                ErrorKind::Unsupported,
+                operation,
+                format!("Process {} has no window or console to close", pid),
+            ));
+        }
+        // Ignore the event ourselves while attached; it is delivered asynchronously
+        let _ = SetConsoleCtrlHandler(None, true);
+        let result = GenerateConsoleCtrlEvent(event, 0);
+        std::thread::sleep(Duration::from_millis(100));
+        let _ = FreeConsole();
+        let _ = SetConsoleCtrlHandler(None, false);
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+
+        result.map_err(|e| {
+            CommandError::from_win32(operation, &e)
+                .context(&format!("Failed to signal process {}", pid))
+        })
+    }
+}
@@ -1,10 +1,11 @@
+use crate::error::{CommandError, CommandResult};
 use crate::models::StartupApp;
 
 #[cfg(target_os = "windows")]
 use winreg::{enums::*, RegKey};
 
 #[tauri::command]
-pub fn get_startup_apps() -> Result<Vec<StartupApp>, String> {
+pub fn get_startup_apps() -> CommandResult<Vec<StartupApp>> {
     let mut apps = Vec::new();
 
     #[cfg(target_os = "windows")]
@@ -95,7 +96,9 @@ pub fn get_startup_apps() -> Result<Vec<StartupApp>, String> {
 }
 
 #[tauri::command]
-pub fn toggle_startup_app(name: String, path: String, enable: bool) -> Result<(), String> {
+pub fn toggle_startup_app(name: String, path: String, enable: bool) -> CommandResult<()> {
+    let io_error = |e: std::io::Error| CommandError::from_io("toggle_startup_app", &e);
+
     #[cfg(target_os = "windows")]
     {
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
@@ -106,20 +109,16 @@ pub fn toggle_startup_app(name: String, path: String, enable: bool) -> Result<()
             // Enable: Add to Run, Remove from Disabled
             let run_key = hkcu
                 .open_subkey_with_flags(run_path, KEY_WRITE)
-                .map_err(|e| e.to_string())?;
-            run_key.set_value(&name, &path).map_err(|e| e.to_string())?;
+                .map_err(io_error)?;
+            run_key.set_value(&name, &path).map_err(io_error)?;
 
             if let Ok(disabled_key) = hkcu.open_subkey_with_flags(disabled_path, KEY_WRITE) {
                 let _ = disabled_key.delete_value(&name);
             }
         } else {
             // Disable: Add to Disabled, Remove from Run
-            let (disabled_key, _) = hkcu
-                .create_subkey(disabled_path)
-                .map_err(|e| e.to_string())?;
-            disabled_key
-                .set_value(&name, &path)
-                .map_err(|e| e.to_string())?;
+            let (disabled_key, _) = hkcu.create_subkey(disabled_path).map_err(io_error)?;
+            disabled_key.set_value(&name, &path).map_err(io_error)?;
 
             if let Ok(run_key) = hkcu.open_subkey_with_flags(run_path, KEY_WRITE) {
                 let _ = run_key.delete_value(&name);
@@ -135,11 +134,13 @@ pub fn toggle_startup_app(name: String, path: String, enable: bool) -> Result<()
             .or_else(|_| {
                 std::env::var("HOME").map(|home| std::path::Path::new(&home).join(".config"))
             })
-            .map_err(|_| "Could not find config directory")?;
+            .map_err(|_| {
+                CommandError::not_found("toggle_startup_app", "Could not find config directory")
+            })?;
 
         let autostart_dir = config_dir.join("autostart");
 
-        let entries = std::fs::read_dir(&autostart_dir).map_err(|e| e.to_string())?;
+        let entries = std::fs::read_dir(&autostart_dir).map_err(io_error)?;
 
         for entry in entries.flatten() {
             let file_path = entry.path();
@@ -174,16 +175,19 @@ pub fn toggle_startup_app(name: String, path: String, enable: bool) -> Result<()
                         new_content.push_str("\nHidden=false\n");
                     }
 
-                    std::fs::write(&file_path, new_content).map_err(|e| e.to_string())?;
+                    std::fs::write(&file_path, new_content).map_err(io_error)?;
                     return Ok(());
                 }
             }
         }
-        return Err("Application entry not found".to_string());
+        return Err(CommandError::not_found(
+            "toggle_startup_app",
+            "Application entry not found",
+        ));
     }
 
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
-        return Err("Unsupported OS".to_string());
+        return Err(CommandError::unsupported("toggle_startup_app"));
     }
 }
@@ -0,0 +1,23 @@
+use crate::error::CommandResult;
+use crate::models::StatsFrame;
+use crate::monitor::stream::SharedStatsStream;
+use crate::monitor::view::ProcessView;
+use tauri::State;
+
+/// The subscribed view as of the last `stats-update`, for a client that is
+/// starting up or has missed a frame
+#[tauri::command]
+pub fn get_stats_snapshot(stream: State<SharedStatsStream>) -> StatsFrame {
+    stream.lock().unwrap().snapshot()
+}
+
+/// Change the sort, filter, shape or window of the streamed process view.
+/// Returns its snapshot; following `stats-update` frames build on it.
+/// An invalid filter query is rejected with `invalid_argument`.
+#[tauri::command]
+pub fn subscribe_process_view(
+    stream: State<SharedStatsStream>,
+    view: ProcessView,
+) -> CommandResult<StatsFrame> {
+    stream.lock().unwrap().subscribe(view)
+}
@@ -0,0 +1,214 @@
+//! Actions on a process together with all of its descendants, so ending a
+//! `npm run dev` does not leave its children orphaned. The tree is the one of the
+//! last sample; every process is checked against the start time it was listed with
+//! right before it is acted on, so a PID reused in the meantime is left alone.
+
+use super::signal::{self, DEFAULT_GRACE, KILL_TIMEOUT, POLL_INTERVAL};
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::identity;
+use crate::models::{ProcessSignal, TreeActionResult, TreeActionStatus};
+use crate::monitor::{Monitor, SharedMonitor};
+use crate::suspend;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+/// A process of the tree as it was listed
+pub struct Target {
+    pid: u32,
+    start_ticks: Option<u64>,
+    name: String,
+}
+
+impl Target {
+    /// The start time to check against, None if the process is gone or its PID reused
+    fn alive(&self) -> Option<u64> {
+        let ticks = self
+            .start_ticks
+            .or_else(|| identity::start_ticks(self.pid))?;
+        identity::is_running(self.pid, ticks).then_some(ticks)
+    }
+
+    fn result(&self, status: TreeActionStatus, error: Option<CommandError>) -> TreeActionResult {
+        TreeActionResult {
+            pid: self.pid,
+            name: self.name.clone(),
+            status,
+            error,
+        }
+    }
+}
+
+/// `pid` and its descendants, every parent before its children
+pub fn targets(
+    monitor: &Monitor,
+    operation: &'static str,
+    pid: u32,
+    start_ticks: Option<u64>,
+) -> CommandResult<Vec<Target>> {
+    identity::verify(operation, pid, start_ticks)?;
+    let live = identity::start_ticks(pid);
+    if live.is_none() {
+        return Err(CommandError::process_not_found(operation, pid));
+    }
+
+    let subtree = monitor.subtree(pid);
+    match subtree.first() {
+        Some(root) if root.start_ticks.is_none() || root.start_ticks == live => Ok(subtree
+            .into_iter()
+            .map(|p| Target {
+                pid: p.pid,
+                start_ticks: p.start_ticks,
+                name: p.name,
+            })
+            .collect()),
+        // The sample predates this process: its children aren't known yet
+        _ => Ok(vec![Target {
+            pid,
+            start_ticks: live,
+            name: String::new(),
+        }]),
+    }
+}
+
+/// Ask every process of the tree to exit, deepest first so parents can't respawn
+/// their children, wait up to `grace_ms` (default 5 s) for all of them, then
+/// force-kill the rest
+#[tauri::command]
+pub async fn terminate_process_tree(
+    monitor: State<'_, SharedMonitor>,
+    pid: u32,
+    start_ticks: Option<u64>,
+    grace_ms: Option<u64>,
+) -> CommandResult<Vec<TreeActionResult>> {
+    let targets = targets(
+        &monitor.lock().unwrap(),
+        "terminate_process_tree",
+        pid,
+        start_ticks,
+    )?;
+    let grace = grace_ms.map(Duration::from_millis).unwrap_or(DEFAULT_GRACE);
+    tauri::async_runtime::spawn_blocking(move || terminate_tree(&targets, grace))
+        .await
+        .map_err(|e| CommandError::new(ErrorKind::Io, "terminate_process_tree", e.to_string()))
+}
+
+/// Suspend every process of the tree (see `crate::suspend`), parents first so none
+/// of them reacts to a stopped child
+#[tauri::command]
+pub fn suspend_process_tree(
+    monitor: State<SharedMonitor>,
+    pid: u32,
+    start_ticks: Option<u64>,
+) -> CommandResult<Vec<TreeActionResult>> {
+    const OPERATION: &str = "suspend_process_tree";
+    let targets = targets(&monitor.lock().unwrap(), OPERATION, pid, start_ticks)?;
+    Ok(act_on_tree(targets.iter(), |pid| {
+        suspend::suspend(pid, OPERATION)
+    }))
+}
+
+/// Resume every process of the tree, children first
+#[tauri::command]
+pub fn resume_process_tree(
+    monitor: State<SharedMonitor>,
+    pid: u32,
+    start_ticks: Option<u64>,
+) -> CommandResult<Vec<TreeActionResult>> {
+    const OPERATION: &str = "resume_process_tree";
+    let targets = targets(&monitor.lock().unwrap(), OPERATION, pid, start_ticks)?;
+    let mut results = act_on_tree(targets.iter().rev(), |pid| suspend::resume(pid, OPERATION));
+    results.reverse();
+    Ok(results)
+}
+
+/// Apply `action` to each process still running, in turn
+pub fn act_on_tree<'a>(
+    targets: impl Iterator<Item = &'a Target>,
+    mut action: impl FnMut(u32) -> CommandResult<()>,
+) -> Vec<TreeActionResult> {
+    targets
+        .map(|target| {
+            if target.alive().is_none() {
+                return target.result(TreeActionStatus::Gone, None);
+            }
+            match action(target.pid) {
+                Ok(()) => target.result(TreeActionStatus::Done, None),
+                Err(_) if target.alive().is_none() => target.result(TreeActionStatus::Gone, None),
+                Err(e) => target.result(TreeActionStatus::Failed, Some(e)),
+            }
+        })
+        .collect()
+}
+
+/// Blocking implementation of `terminate_process_tree`; results are in `targets` order
+pub fn terminate_tree(targets: &[Target], grace: Duration) -> Vec<TreeActionResult> {
+    const OPERATION: &str = "terminate_process_tree";
+
+    let mut results: Vec<Option<TreeActionResult>> = targets.iter().map(|_| None).collect();
+    // Processes asked to exit, deepest first, with the start time they are checked against
+    let mut pending: Vec<(usize, u64)> = Vec::new();
+    for (i, target) in targets.iter().enumerate().rev() {
+        let Some(ticks) = target.alive() else {
+            results[i] = Some(target.result(TreeActionStatus::Gone, None));
+            continue;
+        };
+        match signal::deliver(target.pid, ProcessSignal::Term, OPERATION) {
+            Ok(()) => {
+                signal::resume_suspended(target.pid, ticks, OPERATION);
+                pending.push((i, ticks))
+            }
+            // Can't be asked (see `terminate`): killed once the grace period is over
+            Err(e) if e.kind == ErrorKind::Unsupported => pending.push((i, ticks)),
+            Err(_) if target.alive().is_none() => {
+                results[i] = Some(target.result(TreeActionStatus::Gone, None))
+            }
+            Err(e) => results[i] = Some(target.result(TreeActionStatus::Failed, Some(e))),
+        }
+    }
+
+    let exited = |&(i, ticks): &(usize, u64)| !identity::is_running(targets[i].pid, ticks);
+    let deadline = Instant::now() + grace;
+    loop {
+        pending.retain(|entry| {
+            let gone = exited(entry);
+            if gone {
+                results[entry.0] = Some(targets[entry.0].result(TreeActionStatus::Done, None));
+            }
+            !gone
+        });
+        if pending.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    pending.retain(|&(i, ticks)| {
+        let target = &targets[i];
+        match signal::deliver(target.pid, ProcessSignal::Kill, OPERATION) {
+            Err(e) if identity::is_running(target.pid, ticks) => {
+                results[i] = Some(target.result(TreeActionStatus::Failed, Some(e)));
+                false
+            }
+            _ => true,
+        }
+    });
+    let killed = Instant::now();
+    while pending.iter().any(|entry| !exited(entry)) && killed.elapsed() < KILL_TIMEOUT {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    for entry in &pending {
+        let target = &targets[entry.0];
+        results[entry.0] = Some(if exited(entry) {
+            target.result(TreeActionStatus::Killed, None)
+        } else {
+            let error = CommandError::new(
+                ErrorKind::Io,
+                OPERATION,
+                format!("Process {} is still running after being killed", target.pid),
+            );
+            target.result(TreeActionStatus::Failed, Some(error))
+        });
+    }
+
+    results.into_iter().flatten().collect()
+}
@@ -0,0 +1,43 @@
+use crate::models::AppConfig;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Persisted application settings (`config.json` in the app config directory)
+pub struct ConfigStore {
+    path: PathBuf,
+    config: Mutex<AppConfig>,
+}
+
+impl ConfigStore {
+    /// Load the config file, using defaults for a missing file or missing keys
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let config = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => AppConfig::default(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path,
+            config: Mutex::new(config),
+        })
+    }
+
+    pub fn get(&self) -> AppConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Apply `change` to the current config and write it to disk
+    pub fn update(&self, change: impl FnOnce(&mut AppConfig)) -> io::Result<AppConfig> {
+        let mut config = self.config.lock().unwrap();
+        change(&mut config);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&*config)?)?;
+        Ok(config.clone())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::models::{AlertEvent, AlertSeverity};
+use crate::monitor::{self, MonitorServices, MonitorSink};
+use crate::source;
+use std::path::PathBuf;
+
+// Must match `identifier` in tauri.conf.json so the daemon and the GUI share their files
+const APP_IDENTIFIER: &str = "com.actiometa.actiowatch";
+
+/// Same location as Tauri's `app_data_dir()`
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join(APP_IDENTIFIER))
+}
+
+/// Same location as Tauri's `app_config_dir()`
+fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(APP_IDENTIFIER))
+}
+
+/// No webview to feed: fired alerts are written to stdout (the journal under systemd)
+struct DaemonSink;
+
+impl MonitorSink for DaemonSink {
+    fn alert(&self, event: &AlertEvent) {
+        let severity = match event.severity {
+            AlertSeverity::Info => "INFO",
+            AlertSeverity::Warning => "WARNING",
+            AlertSeverity::Critical => "CRITICAL",
+        };
+        println!("[{}] {}: {}", severity, event.rule_name, event.message);
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Headless mode (`--headless`): monitoring, alerting, history and the metrics
+/// endpoint without a webview or tray, e.g. as a systemd user service.
+pub fn run() {
+    let (Some(data_dir), Some(config_dir)) = (data_dir(), config_dir()) else {
+        eprintln!("Could not determine the data and config directories");
+        std::process::exit(1);
+    };
+
+    tauri::async_runtime::block_on(async move {
+        let services = match MonitorServices::open(&data_dir, &config_dir, source::from_env()) {
+            Ok(services) => services,
+            Err(e) => {
+                eprintln!("Failed to open ActioWatch data: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        println!("ActioWatch running headless (data: {})", data_dir.display());
+
+        tokio::select! {
+            _ = monitor::run(services, DaemonSink) => {}
+            _ = shutdown_signal() => {
+                println!("ActioWatch stopping");
+            }
+        }
+    });
+}
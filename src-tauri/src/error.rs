@@ -0,0 +1,151 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+/// Broad class of a failure, so callers can react without parsing messages
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotFound,
+    PermissionDenied,
+    Unsupported,
+    InvalidArgument,
+    ElevationCancelled,
+    Io,
+}
+
+/// Error returned by every command. Serialized to the frontend as
+/// `{ kind, operation, code, message }`.
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandError {
+    pub kind: ErrorKind,
+    /// What was attempted, e.g. `set_priority`
+    pub operation: &'static str,
+    /// OS error number (errno on Unix, Win32 error code on Windows), if any
+    pub code: Option<i32>,
+    pub message: String,
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    pub fn new(kind: ErrorKind, operation: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            operation,
+            code: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(operation: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, operation, message)
+    }
+
+    pub fn invalid_argument(operation: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidArgument, operation, message)
+    }
+
+    // Only reached on platforms without an implementation of the operation
+    #[allow(dead_code)]
+    pub fn unsupported(operation: &'static str) -> Self {
+        Self::new(
+            ErrorKind::Unsupported,
+            operation,
+            "Not supported on this OS",
+        )
+    }
+
+    pub fn process_not_found(operation: &'static str, pid: u32) -> Self {
+        Self::not_found(operation, format!("Process {} not found", pid))
+    }
+
+    /// Classify an `io::Error`, keeping its raw OS code
+    pub fn from_io(operation: &'static str, err: &io::Error) -> Self {
+        if let Some(code) = err.raw_os_error() {
+            return Self::from_os_code(operation, code, err.to_string());
+        }
+        let kind = match err.kind() {
+            io::ErrorKind::NotFound => ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => ErrorKind::InvalidArgument,
+            io::ErrorKind::Unsupported => ErrorKind::Unsupported,
+            _ => ErrorKind::Io,
+        };
+        Self::new(kind, operation, err.to_string())
+    }
+
+    /// Classify an errno (Unix) or Win32 error code (Windows)
+    pub fn from_os_code(operation: &'static str, code: i32, message: impl Into<String>) -> Self {
+        Self::new(kind_of_os_code(code), operation, message).with_code(code)
+    }
+
+    #[cfg(unix)]
+    pub fn from_errno(operation: &'static str, errno: nix::errno::Errno) -> Self {
+        Self::from_os_code(operation, errno as i32, errno.desc())
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn from_win32(operation: &'static str, err: &windows::core::Error) -> Self {
+        // HRESULT_FROM_WIN32 wraps Win32 codes as 0x8007xxxx; unwrap them for the caller
+        let hresult = err.code().0 as u32;
+        let code = if hresult & 0xFFFF_0000 == 0x8007_0000 {
+            (hresult & 0xFFFF) as i32
+        } else {
+            hresult as i32
+        };
+        Self::from_os_code(operation, code, err.message())
+    }
+
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Prefix the message with what was being done
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+#[cfg(unix)]
+fn kind_of_os_code(code: i32) -> ErrorKind {
+    match code {
+        libc::ESRCH | libc::ENOENT => ErrorKind::NotFound,
+        libc::EPERM | libc::EACCES => ErrorKind::PermissionDenied,
+        libc::EINVAL => ErrorKind::InvalidArgument,
+        libc::ENOSYS | libc::EOPNOTSUPP => ErrorKind::Unsupported,
+        _ => ErrorKind::Io,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn kind_of_os_code(code: i32) -> ErrorKind {
+    match code {
+        // ERROR_FILE_NOT_FOUND, ERROR_PATH_NOT_FOUND
+        2 | 3 => ErrorKind::NotFound,
+        // ERROR_ACCESS_DENIED, ERROR_PRIVILEGE_NOT_HELD
+        5 | 1314 => ErrorKind::PermissionDenied,
+        // ERROR_NOT_SUPPORTED, ERROR_CALL_NOT_IMPLEMENTED
+        50 | 120 => ErrorKind::Unsupported,
+        // ERROR_INVALID_PARAMETER
+        87 => ErrorKind::InvalidArgument,
+        // ERROR_CANCELLED (UAC prompt dismissed)
+        1223 => ErrorKind::ElevationCancelled,
+        _ => ErrorKind::Io,
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn kind_of_os_code(_code: i32) -> ErrorKind {
+    ErrorKind::Io
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
@@ -0,0 +1,488 @@
+use crate::error::{CommandError, CommandResult};
+use crate::models::{ExporterConfig, ProcessInfo, SystemStats};
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+// How long `apply` waits for the previous endpoint to release its address
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Optional OpenMetrics endpoint serving the latest sample produced by the monitor loop.
+#[derive(Default)]
+pub struct MetricsExporter {
+    latest: Arc<RwLock<Option<SystemStats>>>,
+    server: Mutex<Option<Server>>, // Only held briefly: `publish` takes it on every sample
+    applying: Mutex<()>,           // Held by `apply` throughout, one change at a time
+}
+
+/// The running endpoint
+struct Server {
+    address: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    closed: mpsc::Receiver<()>, // Disconnects once the accept loop has dropped the listener
+}
+
+impl Server {
+    /// Stop accepting and wait until the listener is closed, so its address can be
+    /// bound again right away
+    fn stop(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.closed.recv_timeout(SHUTDOWN_TIMEOUT);
+    }
+}
+
+impl MetricsExporter {
+    /// Store the latest sample for scrapes. Does nothing while the endpoint is disabled.
+    pub fn publish(&self, stats: &SystemStats) {
+        if self.server.lock().unwrap().is_some() {
+            *self.latest.write().unwrap() = Some(stats.clone());
+        }
+    }
+
+    /// Start, stop or move the endpoint to match `config`. A running endpoint whose
+    /// address is unchanged keeps its listener. Blocks while a moved endpoint shuts down.
+    pub fn apply(&self, config: &ExporterConfig) -> CommandResult<()> {
+        let address = if config.enabled {
+            let address: SocketAddr = config.address.parse().map_err(|_| {
+                CommandError::invalid_argument(
+                    "start_exporter",
+                    format!("Invalid exporter address: {}", config.address),
+                )
+            })?;
+            Some(address)
+        } else {
+            None
+        };
+
+        let _applying = self.applying.lock().unwrap();
+        let running = {
+            let mut server = self.server.lock().unwrap();
+            if server.as_ref().map(|s| s.address) == address && address.is_some() {
+                return Ok(());
+            }
+            server.take()
+        };
+        // Wait for the old listener without the lock, so samples keep being published
+        if let Some(running) = running {
+            running.stop();
+        }
+        *self.latest.write().unwrap() = None;
+
+        let Some(address) = address else {
+            return Ok(());
+        };
+
+        // Bind synchronously so address errors are reported to the caller
+        let listener = std::net::TcpListener::bind(address).map_err(|e| {
+            CommandError::from_io("start_exporter", &e)
+                .context(&format!("Failed to listen on {}", address))
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| CommandError::from_io("start_exporter", &e))?;
+
+        let latest = self.latest.clone();
+        let (shutdown, mut stopped) = oneshot::channel();
+        let (closing, closed) = mpsc::channel::<()>();
+        tauri::async_runtime::spawn(async move {
+            // Dropped with the listener when the task ends
+            let _closing = closing;
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    eprintln!("Failed to start metrics exporter: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    accepted = listener.accept() => {
+                        if let Ok((stream, _)) = accepted {
+                            let latest = latest.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = handle_connection(stream, &latest).await {
+                                    eprintln!("Metrics exporter connection error: {}", e);
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+        });
+        *self.server.lock().unwrap() = Some(Server {
+            address,
+            shutdown,
+            closed,
+        });
+
+        Ok(())
+    }
+}
+
+async fn handle_connection(
+    mut stream: tokio::net::TcpStream,
+    latest: &RwLock<Option<SystemStats>>,
+) -> std::io::Result<()> {
+    // Only the request line matters; scrapers send small GET requests
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let path = request_line.next().unwrap_or("");
+
+    let (status, content_type, body) = if method != "GET" {
+        ("405 Method Not Allowed", "text/plain", String::new())
+    } else if path == "/metrics" || path.starts_with("/metrics?") {
+        let body = match latest.read().unwrap().as_ref() {
+            Some(stats) => render(stats),
+            None => "# EOF\n".to_string(),
+        };
+        ("200 OK", CONTENT_TYPE, body)
+    } else {
+        ("404 Not Found", "text/plain", "Not Found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, unit: Option<&str>, help: &str) {
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    if let Some(unit) = unit {
+        let _ = writeln!(out, "# UNIT {} {}", name, unit);
+    }
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+}
+
+fn flatten<'a>(nodes: &'a [ProcessInfo], out: &mut Vec<&'a ProcessInfo>) {
+    for node in nodes {
+        out.push(node);
+        flatten(&node.children, out);
+    }
+}
+
+// Metric name, unit, help text and value accessor of a per-process family
+type ProcessMetric = (
+    &'static str,
+    Option<&'static str>,
+    &'static str,
+    fn(&ProcessInfo) -> String,
+);
+
+/// Render a sample in the OpenMetrics text format
+pub fn render(stats: &SystemStats) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "actiowatch_cpu_usage_percent",
+        Some("percent"),
+        "Global CPU usage.",
+    );
+    let _ = writeln!(out, "actiowatch_cpu_usage_percent {}", stats.cpu_usage);
+
+    family(
+        &mut out,
+        "actiowatch_cpu_core_usage_percent",
+        Some("percent"),
+        "CPU usage per core.",
+    );
+    for (core, usage) in stats.cpu_cores.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "actiowatch_cpu_core_usage_percent{{core=\"{}\"}} {}",
+            core, usage
+        );
+    }
+
+    family(
+        &mut out,
+        "actiowatch_memory_used_bytes",
+        Some("bytes"),
+        "Used system memory.",
+    );
+    let _ = writeln!(out, "actiowatch_memory_used_bytes {}", stats.memory_used);
+    family(
+        &mut out,
+        "actiowatch_memory_total_bytes",
+        Some("bytes"),
+        "Total system memory.",
+    );
+    let _ = writeln!(out, "actiowatch_memory_total_bytes {}", stats.memory_total);
+
+    family(
+        &mut out,
+        "actiowatch_network_transmit_bytes_per_second",
+        None,
+        "Network upload rate over all interfaces.",
+    );
+    let _ = writeln!(
+        out,
+        "actiowatch_network_transmit_bytes_per_second {}",
+        stats.network_up
+    );
+    family(
+        &mut out,
+        "actiowatch_network_receive_bytes_per_second",
+        None,
+        "Network download rate over all interfaces.",
+    );
+    let _ = writeln!(
+        out,
+        "actiowatch_network_receive_bytes_per_second {}",
+        stats.network_down
+    );
+
+    family(
+        &mut out,
+        "actiowatch_disk_read_bytes_per_second",
+        None,
+        "Disk read rate over all processes.",
+    );
+    let _ = writeln!(
+        out,
+        "actiowatch_disk_read_bytes_per_second {}",
+        stats.disk_read
+    );
+    family(
+        &mut out,
+        "actiowatch_disk_write_bytes_per_second",
+        None,
+        "Disk write rate over all processes.",
+    );
+    let _ = writeln!(
+        out,
+        "actiowatch_disk_write_bytes_per_second {}",
+        stats.disk_write
+    );
+
+    family(
+        &mut out,
+        "actiowatch_processes",
+        None,
+        "Number of processes.",
+    );
+    let _ = writeln!(out, "actiowatch_processes {}", stats.process_count);
+
+    family(
+        &mut out,
+        "actiowatch_sensor_temperature_celsius",
+        Some("celsius"),
+        "Current sensor temperature.",
+    );
+    for c in &stats.components {
+        let _ = writeln!(
+            out,
+            "actiowatch_sensor_temperature_celsius{{sensor=\"{}\"}} {}",
+            escape_label(&c.label),
+            c.temperature
+        );
+    }
+    family(
+        &mut out,
+        "actiowatch_sensor_max_temperature_celsius",
+        Some("celsius"),
+        "Highest temperature seen by the sensor.",
+    );
+    for c in &stats.components {
+        let _ = writeln!(
+            out,
+            "actiowatch_sensor_max_temperature_celsius{{sensor=\"{}\"}} {}",
+            escape_label(&c.label),
+            c.max_temperature
+        );
+    }
+    family(
+        &mut out,
+        "actiowatch_sensor_critical_temperature_celsius",
+        Some("celsius"),
+        "Critical temperature reported by the sensor.",
+    );
+    for c in &stats.components {
+        if let Some(critical) = c.critical_temperature {
+            let _ = writeln!(
+                out,
+                "actiowatch_sensor_critical_temperature_celsius{{sensor=\"{}\"}} {}",
+                escape_label(&c.label),
+                critical
+            );
+        }
+    }
+
+    let mut processes = Vec::new();
+    flatten(&stats.top_processes, &mut processes);
+
+    let per_process: [ProcessMetric; 5] = [
+        (
+            "actiowatch_process_cpu_usage_percent",
+            Some("percent"),
+            "CPU usage of the process, normalized to all cores.",
+            |p| p.cpu_usage.to_string(),
+        ),
+        (
+            "actiowatch_process_memory_bytes",
+            Some("bytes"),
+            "Resident memory of the process.",
+            |p| p.memory_usage.to_string(),
+        ),
+        (
+            "actiowatch_process_disk_read_bytes_per_second",
+            None,
+            "Disk read rate of the process.",
+            |p| p.disk_read.to_string(),
+        ),
+        (
+            "actiowatch_process_disk_write_bytes_per_second",
+            None,
+            "Disk write rate of the process.",
+            |p| p.disk_write.to_string(),
+        ),
+        (
+            "actiowatch_process_threads",
+            None,
+            "Number of threads of the process.",
+            |p| p.thread_count.to_string(),
+        ),
+    ];
+
+    for (name, unit, help, value) in per_process {
+        family(&mut out, name, unit, help);
+        for p in &processes {
+            let _ = writeln!(
+                out,
+                "{}{{pid=\"{}\",name=\"{}\"}} {}",
+                name,
+                p.pid,
+                escape_label(&p.name),
+                value(p)
+            );
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ComponentInfo;
+    use std::io::{Read, Write};
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.into(),
+            start_time: 0,
+            start_ticks: None,
+            user: String::new(),
+            exe: String::new(),
+            cpu_usage: 12.5,
+            total_cpu_usage: 12.5,
+            memory_usage: 4096,
+            total_memory_usage: 4096,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            total_disk_read: 0,
+            total_disk_write: 0,
+            thread_count: 3,
+            suspended: false,
+            children: Vec::new(),
+        }
+    }
+
+    fn scrape(address: SocketAddr) -> String {
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn renders_families_and_escaped_labels() {
+        let mut parent = process(1, "init");
+        parent.children.push(process(42, "say \"hi\"\\now\nbye"));
+        let stats = SystemStats {
+            cpu_usage: 50.0,
+            components: vec![ComponentInfo {
+                label: "coretemp \"Package\"".into(),
+                temperature: 45.0,
+                max_temperature: 80.0,
+                critical_temperature: None,
+            }],
+            top_processes: vec![parent],
+            ..Default::default()
+        };
+
+        let out = render(&stats);
+        assert!(out.ends_with("\n# EOF\n"));
+        assert_eq!(out.matches("# EOF").count(), 1);
+        assert!(out.contains(
+            "# TYPE actiowatch_cpu_usage_percent gauge\n\
+             # UNIT actiowatch_cpu_usage_percent percent\n\
+             # HELP actiowatch_cpu_usage_percent Global CPU usage.\n\
+             actiowatch_cpu_usage_percent 50\n"
+        ));
+        assert!(out.contains("# TYPE actiowatch_processes gauge\n# HELP actiowatch_processes "));
+        assert!(out.contains(
+            "actiowatch_sensor_temperature_celsius{sensor=\"coretemp \\\"Package\\\"\"} 45\n"
+        ));
+        assert!(out.contains(
+            "actiowatch_process_threads{pid=\"42\",name=\"say \\\"hi\\\"\\\\now\\nbye\"} 3\n"
+        ));
+        // Children are flattened next to their parents
+        assert!(out.contains("actiowatch_process_threads{pid=\"1\",name=\"init\"} 3\n"));
+        // No critical temperature, no sample
+        assert!(!out.contains("actiowatch_sensor_critical_temperature_celsius{"));
+    }
+
+    #[test]
+    fn rebinds_the_same_address() {
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let config = |enabled| ExporterConfig {
+            enabled,
+            address: address.to_string(),
+        };
+        let exporter = MetricsExporter::default();
+
+        exporter.apply(&config(true)).unwrap();
+        exporter.apply(&config(true)).unwrap();
+        exporter.apply(&config(false)).unwrap();
+        assert!(std::net::TcpStream::connect(address).is_err());
+        exporter.apply(&config(true)).unwrap();
+
+        exporter.publish(&SystemStats::default());
+        let response = scrape(address);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("# EOF\n"));
+    }
+}
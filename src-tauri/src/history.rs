@@ -0,0 +1,580 @@
+use crate::models::{HistorySample, SensorReading, SystemStats};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECOND_MS: u64 = 1_000;
+const MINUTE_MS: u64 = 60 * SECOND_MS;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+// Upper bound of points returned by an "auto" query before falling back to a coarser tier
+const MAX_AUTO_POINTS: u64 = 5_000;
+
+/// Downsampling tier. Each tier lives in its own directory, split into segment files
+/// named after the segment start (Unix ms) containing one JSON sample per line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Resolution {
+    Second,
+    Minute,
+    Hour,
+}
+
+impl Resolution {
+    const ALL: [Resolution; 3] = [Resolution::Second, Resolution::Minute, Resolution::Hour];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "1s" => Some(Resolution::Second),
+            "1m" => Some(Resolution::Minute),
+            "1h" => Some(Resolution::Hour),
+            _ => None,
+        }
+    }
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            Resolution::Second => "1s",
+            Resolution::Minute => "1m",
+            Resolution::Hour => "1h",
+        }
+    }
+
+    fn step_ms(self) -> u64 {
+        match self {
+            Resolution::Second => SECOND_MS,
+            Resolution::Minute => MINUTE_MS,
+            Resolution::Hour => HOUR_MS,
+        }
+    }
+
+    fn retention_ms(self) -> u64 {
+        match self {
+            Resolution::Second => HOUR_MS,
+            Resolution::Minute => 7 * DAY_MS,
+            Resolution::Hour => 365 * DAY_MS,
+        }
+    }
+
+    fn segment_ms(self) -> u64 {
+        match self {
+            Resolution::Second => 10 * MINUTE_MS,
+            Resolution::Minute => DAY_MS,
+            Resolution::Hour => 30 * DAY_MS,
+        }
+    }
+}
+
+/// Running average of the samples falling into one downsampled bucket
+struct Bucket {
+    start: u64,
+    count: u64,
+    cpu_usage: f64,
+    memory_used: u128,
+    memory_total: u64,
+    network_up: u128,
+    network_down: u128,
+    disk_read: u128,
+    disk_write: u128,
+    sensors: Vec<(String, f64, u64)>,
+}
+
+impl Bucket {
+    fn new(start: u64) -> Self {
+        Self {
+            start,
+            count: 0,
+            cpu_usage: 0.0,
+            memory_used: 0,
+            memory_total: 0,
+            network_up: 0,
+            network_down: 0,
+            disk_read: 0,
+            disk_write: 0,
+            sensors: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, sample: &HistorySample) {
+        self.count += 1;
+        self.cpu_usage += sample.cpu_usage as f64;
+        self.memory_used += sample.memory_used as u128;
+        self.memory_total = sample.memory_total;
+        self.network_up += sample.network_up as u128;
+        self.network_down += sample.network_down as u128;
+        self.disk_read += sample.disk_read as u128;
+        self.disk_write += sample.disk_write as u128;
+
+        for sensor in &sample.sensors {
+            match self.sensors.iter_mut().find(|(l, _, _)| *l == sensor.label) {
+                Some((_, sum, count)) => {
+                    *sum += sensor.temperature as f64;
+                    *count += 1;
+                }
+                None => self
+                    .sensors
+                    .push((sensor.label.clone(), sensor.temperature as f64, 1)),
+            }
+        }
+    }
+
+    fn average(&self) -> HistorySample {
+        let n = self.count.max(1);
+        HistorySample {
+            timestamp: self.start,
+            cpu_usage: (self.cpu_usage / n as f64) as f32,
+            memory_used: (self.memory_used / n as u128) as u64,
+            memory_total: self.memory_total,
+            network_up: (self.network_up / n as u128) as u64,
+            network_down: (self.network_down / n as u128) as u64,
+            disk_read: (self.disk_read / n as u128) as u64,
+            disk_write: (self.disk_write / n as u128) as u64,
+            sensors: self
+                .sensors
+                .iter()
+                .map(|(label, sum, count)| SensorReading {
+                    label: label.clone(),
+                    temperature: (sum / *count as f64) as f32,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistoryState {
+    // Open bucket of each tier
+    buckets: HashMap<&'static str, Bucket>,
+    // Segment currently being appended to, per tier
+    segments: HashMap<&'static str, u64>,
+}
+
+/// On-disk store of global system samples averaged into 1s / 1m / 1h tiers.
+/// Monitor ticks can be shorter than a second, so even the finest tier is averaged.
+pub struct HistoryStore {
+    dir: PathBuf,
+    state: Mutex<HistoryState>,
+}
+
+/// The global readings of a sample that are kept
+pub fn sample_of(stats: &SystemStats) -> HistorySample {
+    HistorySample {
+        timestamp: stats.timestamp,
+        cpu_usage: stats.cpu_usage,
+        memory_used: stats.memory_used,
+        memory_total: stats.memory_total,
+        network_up: stats.network_up,
+        network_down: stats.network_down,
+        disk_read: stats.disk_read,
+        disk_write: stats.disk_write,
+        sensors: stats
+            .components
+            .iter()
+            .map(|c| SensorReading {
+                label: c.label.clone(),
+                temperature: c.temperature,
+            })
+            .collect(),
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl HistoryStore {
+    pub fn new(dir: PathBuf) -> io::Result<Self> {
+        for resolution in Resolution::ALL {
+            fs::create_dir_all(dir.join(resolution.dir_name()))?;
+        }
+
+        let store = Self {
+            dir,
+            state: Mutex::new(HistoryState::default()),
+        };
+
+        let now = now_ms();
+        for resolution in Resolution::ALL {
+            store.prune(resolution, now)?;
+        }
+        store.restore()?;
+
+        Ok(store)
+    }
+
+    /// Record one monitor tick. Each tier writes out its bucket whenever it rolls over.
+    pub fn record(&self, sample: &HistorySample) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        for resolution in Resolution::ALL {
+            self.accumulate(&mut state, resolution, sample)?;
+        }
+        Ok(())
+    }
+
+    /// Add a sample to the open bucket of a tier, first writing out the previous
+    /// bucket if the sample starts a new one
+    fn accumulate(
+        &self,
+        state: &mut HistoryState,
+        resolution: Resolution,
+        sample: &HistorySample,
+    ) -> io::Result<()> {
+        let bucket_start = sample.timestamp - sample.timestamp % resolution.step_ms();
+        let key = resolution.dir_name();
+
+        let finished = match state.buckets.get(key) {
+            Some(bucket) if bucket.start != bucket_start => state.buckets.remove(key),
+            _ => None,
+        };
+        if let Some(bucket) = finished {
+            self.append(state, resolution, &bucket.average())?;
+        }
+
+        state
+            .buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(bucket_start))
+            .add(sample);
+        Ok(())
+    }
+
+    /// Rebuild the open buckets of the 1m and 1h tiers from the 1s one. They are only
+    /// written out when they roll over, so without this the samples of the last
+    /// minute and hour before a restart would never reach them. An hour bucket older
+    /// than the 1s retention comes back partial.
+    fn restore(&self) -> io::Result<()> {
+        let raw = self.query(0, u64::MAX, Some(Resolution::Second))?;
+        let mut state = self.state.lock().unwrap();
+        for resolution in [Resolution::Minute, Resolution::Hour] {
+            // Buckets up to the last one written out are complete
+            let written = self.latest(resolution)?;
+            for sample in &raw {
+                let bucket_start = sample.timestamp - sample.timestamp % resolution.step_ms();
+                if written.is_none_or(|written| bucket_start > written) {
+                    self.accumulate(&mut state, resolution, sample)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Timestamp of the newest sample of a tier
+    fn latest(&self, resolution: Resolution) -> io::Result<Option<u64>> {
+        let segments = list_segments(&self.dir.join(resolution.dir_name()))?;
+        let Some((_, path)) = segments.last() else {
+            return Ok(None);
+        };
+        let file = fs::File::open(path)?;
+        let mut latest = None;
+        for line in io::BufReader::new(file).lines() {
+            if let Ok(sample) = serde_json::from_str::<HistorySample>(&line?) {
+                latest = latest.max(Some(sample.timestamp));
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Return samples in `[start, end]` (Unix ms). Without an explicit resolution the
+    /// finest tier that still covers `start` and stays under `MAX_AUTO_POINTS` is used.
+    pub fn query(
+        &self,
+        start: u64,
+        end: u64,
+        resolution: Option<Resolution>,
+    ) -> io::Result<Vec<HistorySample>> {
+        let resolution = resolution.unwrap_or_else(|| Self::pick_resolution(start, end));
+        let tier_dir = self.dir.join(resolution.dir_name());
+
+        let mut samples = Vec::new();
+        for (segment_start, path) in list_segments(&tier_dir)? {
+            if segment_start > end || segment_start + resolution.segment_ms() < start {
+                continue;
+            }
+
+            let file = fs::File::open(&path)?;
+            for line in io::BufReader::new(file).lines() {
+                let line = line?;
+                // Skip lines truncated by a crash instead of failing the whole query
+                if let Ok(sample) = serde_json::from_str::<HistorySample>(&line) {
+                    if sample.timestamp >= start && sample.timestamp <= end {
+                        samples.push(sample);
+                    }
+                }
+            }
+        }
+
+        samples.sort_by_key(|s| s.timestamp);
+        Ok(samples)
+    }
+
+    /// Oldest and newest timestamps available across all tiers
+    pub fn bounds(&self) -> io::Result<Option<(u64, u64)>> {
+        let mut oldest: Option<u64> = None;
+        for resolution in Resolution::ALL {
+            if let Some((start, _)) = list_segments(&self.dir.join(resolution.dir_name()))?
+                .into_iter()
+                .next()
+            {
+                oldest = Some(oldest.map_or(start, |o| o.min(start)));
+            }
+        }
+
+        Ok(oldest.map(|o| (o, now_ms())))
+    }
+
+    fn pick_resolution(start: u64, end: u64) -> Resolution {
+        let age = now_ms().saturating_sub(start);
+        let span = end.saturating_sub(start);
+
+        Resolution::ALL
+            .into_iter()
+            .find(|r| age <= r.retention_ms() && span / r.step_ms() <= MAX_AUTO_POINTS)
+            .unwrap_or(Resolution::Hour)
+    }
+
+    fn append(
+        &self,
+        state: &mut HistoryState,
+        resolution: Resolution,
+        sample: &HistorySample,
+    ) -> io::Result<()> {
+        let segment_start = sample.timestamp - sample.timestamp % resolution.segment_ms();
+        let key = resolution.dir_name();
+
+        // Drop expired segments whenever a tier starts a new one
+        if state.segments.insert(key, segment_start) != Some(segment_start) {
+            self.prune(resolution, sample.timestamp)?;
+        }
+
+        let path = self.dir.join(key).join(format!("{}.jsonl", segment_start));
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(sample)?;
+        writeln!(file, "{}", line)
+    }
+
+    fn prune(&self, resolution: Resolution, now: u64) -> io::Result<()> {
+        let cutoff = now.saturating_sub(resolution.retention_ms());
+        for (segment_start, path) in list_segments(&self.dir.join(resolution.dir_name()))? {
+            if segment_start + resolution.segment_ms() < cutoff {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HistoryStore {
+    /// Write out the open second, which `restore` can't rebuild
+    fn drop(&mut self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if let Some(bucket) = state.buckets.remove(Resolution::Second.dir_name()) {
+            if let Err(e) = self.append(&mut state, Resolution::Second, &bucket.average()) {
+                eprintln!("Failed to write history: {}", e);
+            }
+        }
+    }
+}
+
+/// Segment files of a tier directory, sorted by segment start
+fn list_segments(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut segments: Vec<(u64, PathBuf)> = fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                return None;
+            }
+            let start = path.file_stem()?.to_str()?.parse::<u64>().ok()?;
+            Some((start, path))
+        })
+        .collect();
+
+    segments.sort_by_key(|(start, _)| *start);
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty store in its own temporary directory, removed when dropped
+    struct TempStore {
+        store: Option<HistoryStore>,
+        dir: PathBuf,
+    }
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "actiowatch-history-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            Self {
+                store: Some(HistoryStore::new(dir.clone()).unwrap()),
+                dir,
+            }
+        }
+
+        fn store(&self) -> &HistoryStore {
+            self.store.as_ref().unwrap()
+        }
+
+        /// Drop the store (and its open buckets) and load it again
+        fn reopen(&mut self) {
+            self.store = None;
+            self.store = Some(HistoryStore::new(self.dir.clone()).unwrap());
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn sample(timestamp: u64, cpu_usage: f32) -> HistorySample {
+        sample_of(&SystemStats {
+            timestamp,
+            cpu_usage,
+            memory_total: 100,
+            ..Default::default()
+        })
+    }
+
+    /// Start of a recent minute, well within every retention
+    fn recent_minute() -> u64 {
+        let now = now_ms() - 30 * MINUTE_MS;
+        now - now % MINUTE_MS
+    }
+
+    fn raw(store: &HistoryStore) -> Vec<HistorySample> {
+        store.query(0, u64::MAX, Some(Resolution::Second)).unwrap()
+    }
+
+    fn cpu_at(samples: &[HistorySample]) -> Vec<(u64, f32)> {
+        samples.iter().map(|s| (s.timestamp, s.cpu_usage)).collect()
+    }
+
+    #[test]
+    fn minutes_are_averaged_when_they_roll_over() {
+        let temp = TempStore::new("downsample");
+        let store = temp.store();
+        let base = recent_minute();
+
+        for (offset, cpu) in [(0, 10.0), (20, 20.0), (40, 30.0), (60, 50.0), (90, 70.0)] {
+            store
+                .record(&sample(base + offset * SECOND_MS, cpu))
+                .unwrap();
+        }
+
+        // The last second is still open
+        assert_eq!(raw(store).len(), 4);
+
+        // The second minute is still open
+        let minutes = store.query(0, u64::MAX, Some(Resolution::Minute)).unwrap();
+        assert_eq!(cpu_at(&minutes), [(base, 20.0)]);
+        assert_eq!(minutes[0].memory_total, 100);
+
+        store.record(&sample(base + 120 * SECOND_MS, 0.0)).unwrap();
+        let minutes = store.query(0, u64::MAX, Some(Resolution::Minute)).unwrap();
+        assert_eq!(cpu_at(&minutes), [(base, 20.0), (base + MINUTE_MS, 60.0)]);
+    }
+
+    #[test]
+    fn open_buckets_survive_a_restart() {
+        let mut temp = TempStore::new("restore");
+        let base = recent_minute();
+
+        temp.store().record(&sample(base, 10.0)).unwrap();
+        temp.store()
+            .record(&sample(base + 30 * SECOND_MS, 30.0))
+            .unwrap();
+        temp.reopen();
+        temp.store()
+            .record(&sample(base + 45 * SECOND_MS, 50.0))
+            .unwrap();
+        temp.store()
+            .record(&sample(base + 60 * SECOND_MS, 0.0))
+            .unwrap();
+
+        let minutes = temp
+            .store()
+            .query(0, u64::MAX, Some(Resolution::Minute))
+            .unwrap();
+        assert_eq!(cpu_at(&minutes), [(base, 30.0)]);
+
+        // Buckets already written out are not counted twice
+        temp.reopen();
+        temp.store()
+            .record(&sample(base + 120 * SECOND_MS, 0.0))
+            .unwrap();
+        let minutes = temp
+            .store()
+            .query(0, u64::MAX, Some(Resolution::Minute))
+            .unwrap();
+        assert_eq!(cpu_at(&minutes), [(base, 30.0), (base + MINUTE_MS, 0.0)]);
+    }
+
+    #[test]
+    fn expired_segments_are_trimmed() {
+        let mut temp = TempStore::new("retention");
+        let now = now_ms();
+        let now = now - now % SECOND_MS;
+        let expired = now - 2 * HOUR_MS;
+
+        temp.store().record(&sample(expired, 10.0)).unwrap();
+        temp.store().record(&sample(now, 20.0)).unwrap();
+        assert_eq!(cpu_at(&raw(temp.store())), [(expired, 10.0)]);
+
+        // Starting a new segment drops the ones past the retention
+        temp.store().record(&sample(now + SECOND_MS, 30.0)).unwrap();
+        assert_eq!(cpu_at(&raw(temp.store())), [(now, 20.0)]);
+
+        // So does loading the store; closing it writes out the open second
+        temp.store = None;
+        let stale = temp.dir.join("1s").join(format!(
+            "{}.jsonl",
+            expired - expired % Resolution::Second.segment_ms()
+        ));
+        fs::write(
+            &stale,
+            serde_json::to_string(&sample(expired, 10.0)).unwrap(),
+        )
+        .unwrap();
+        temp.reopen();
+        assert!(!stale.exists());
+        assert_eq!(
+            cpu_at(&raw(temp.store())),
+            [(now, 20.0), (now + SECOND_MS, 30.0)]
+        );
+    }
+
+    #[test]
+    fn ticks_under_a_second_are_averaged() {
+        let temp = TempStore::new("subsecond");
+        let store = temp.store();
+        let base = recent_minute();
+
+        for (offset, cpu) in [(0, 10.0), (250, 20.0), (500, 30.0), (750, 40.0)] {
+            store.record(&sample(base + offset, cpu)).unwrap();
+        }
+        for (offset, cpu) in [(1_000, 50.0), (1_500, 70.0), (2_000, 0.0)] {
+            store.record(&sample(base + offset, cpu)).unwrap();
+        }
+
+        assert_eq!(
+            cpu_at(&raw(store)),
+            [(base, 25.0), (base + SECOND_MS, 60.0)]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Process identity: a PID together with the process's start time in the finest
+//! unit the platform offers, so a PID the OS has handed to a new process is not
+//! mistaken for the old one.
+//!
+//! - Linux: clock ticks since boot (`/proc/<pid>/stat` field 22)
+//! - Windows: creation time in 100 ns units since 1601
+//! - elsewhere: Unix start time in seconds
+
+use crate::error::{CommandError, CommandResult};
+
+/// Start time of a running process, None if it is gone or can't be read
+#[cfg(target_os = "linux")]
+pub fn start_ticks(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat(&stat)
+}
+
+/// Field 22 (`starttime`) of a `/proc/<pid>/stat` line. The command name (field 2)
+/// may contain spaces and parentheses, so fields are counted from its closing `)`.
+#[cfg(target_os = "linux")]
+fn parse_stat(stat: &str) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    // `rest` starts at field 3 (state)
+    rest.split_whitespace().nth(22 - 3)?.parse().ok()
+}
+
+#[cfg(target_os = "windows")]
+pub fn start_ticks(pid: u32) -> Option<u64> {
+    use windows::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let (mut creation, mut exit, mut kernel, mut user) = (
+            FILETIME::default(),
+            FILETIME::default(),
+            FILETIME::default(),
+            FILETIME::default(),
+        );
+        let result = GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user);
+        let _ = CloseHandle(handle);
+        result.ok()?;
+        Some(((creation.dwHighDateTime as u64) << 32) | creation.dwLowDateTime as u64)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn start_ticks(pid: u32) -> Option<u64> {
+    use sysinfo::{Pid, System};
+
+    let mut sys = System::new();
+    let pid = Pid::from_u32(pid);
+    sys.refresh_process(pid);
+    sys.process(pid).map(|p| p.start_time())
+}
+
+/// Whether the process that started at `ticks` is still running. On Linux an exited
+/// process waiting for its parent to reap it (a zombie) counts as gone.
+#[cfg(target_os = "linux")]
+pub fn is_running(pid: u32, ticks: u64) -> bool {
+    let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+        return false;
+    };
+    let zombie = stat
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        == Some("Z");
+    !zombie && parse_stat(&stat) == Some(ticks)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_running(pid: u32, ticks: u64) -> bool {
+    start_ticks(pid) == Some(ticks)
+}
+
+/// Check that `pid` still names the process that started at `expected` before acting
+/// on it. A recycled PID is reported as the old process not being found. Without an
+/// expected start time (scripts, older clients) the PID is taken as is.
+///
+/// This narrows the window for PID reuse to the time between the check and the action.
+pub fn verify(operation: &'static str, pid: u32, expected: Option<u64>) -> CommandResult<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    match start_ticks(pid) {
+        Some(actual) if actual == expected => Ok(()),
+        Some(_) => Err(CommandError::not_found(
+            operation,
+            format!(
+                "Process {} has exited; the PID now belongs to another process",
+                pid
+            ),
+        )),
+        None => Err(CommandError::process_not_found(operation, pid)),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    // Fields 3 to 22 after the command name; starttime is the last one
+    const AFTER_COMM: &str = "S 1 123 123 0 -1 4194560 100 0 0 0 5 3 0 0 20 0 1 0 987654";
+
+    #[test]
+    fn start_time_follows_the_last_parenthesis() {
+        let stat = format!(
+            "123 (a) b (c)) {} 4096 200 18446744073709551615\n",
+            AFTER_COMM
+        );
+        assert_eq!(parse_stat(&stat), Some(987654));
+        let stat = format!("42 (my app) {}", AFTER_COMM);
+        assert_eq!(parse_stat(&stat), Some(987654));
+    }
+
+    #[test]
+    fn truncated_or_invalid_stat_is_none() {
+        assert_eq!(parse_stat(""), None);
+        assert_eq!(parse_stat("123 (no closing parenthesis S 1 2 3"), None);
+        assert_eq!(parse_stat("123 (short) S 1 123 123 0 -1"), None);
+        let stat = format!("123 (bad) {}", AFTER_COMM.replace("987654", "98x"));
+        assert_eq!(parse_stat(&stat), None);
+    }
+}
@@ -1,15 +1,40 @@
+mod alerts;
+mod cli;
 mod commands;
+mod config;
+mod daemon;
+mod error;
+mod exporter;
+mod history;
+mod identity;
 mod models;
 mod monitor;
+mod query;
+mod source;
+mod suspend;
 mod tray;
+mod tui;
 
+use commands::alerts::{
+    clear_alert_history, delete_alert_rule, get_alert_history, get_alert_rules, save_alert_rule,
+};
+use commands::history::{get_history, get_history_range};
+use commands::io_priority::{get_process_io_priority, set_process_io_priority};
+use commands::memory::get_process_memory_map;
 use commands::network::get_open_ports;
 use commands::process::{
-    get_process_affinity, get_process_details, get_process_modules, kill_process,
-    set_process_affinity, set_process_priority,
+    get_process_affinity, get_process_details, get_process_groups, kill_process, resume_process,
+    set_process_affinity, set_process_priority, suspend_process,
+};
+use commands::scheduling::{get_process_scheduling, set_process_scheduling};
+use commands::settings::{
+    get_exporter_config, get_notifications_enabled, get_sampling_interval, set_exporter_config,
+    set_notifications_enabled, set_sampling_interval,
 };
-use commands::settings::{get_notifications_enabled, set_notifications_enabled};
+use commands::signal::{send_signal, terminate_process};
 use commands::startup::{get_startup_apps, toggle_startup_app};
+use commands::stats::{get_stats_snapshot, subscribe_process_view};
+use commands::tree::{resume_process_tree, suspend_process_tree, terminate_process_tree};
 use commands::view::get_current_view;
 use models::AppLifecycle;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -18,6 +43,17 @@ use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 && cli::is_cli_command(&args[1]) {
+        // One-shot query, e.g. `actiowatch ports --port 8080 --json`
+        std::process::exit(cli::run(&args[1..]));
+    }
+    if args.iter().any(|a| a == "--headless" || a == "--daemon") {
+        // No webview, no tray: only the monitoring core
+        daemon::run();
+        return;
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_os::init())
@@ -28,17 +64,42 @@ pub fn run() {
         ))
         .invoke_handler(tauri::generate_handler![
             kill_process,
+            suspend_process,
+            resume_process,
             get_process_details,
-            get_process_modules,
+            get_process_groups,
+            get_process_memory_map,
+            send_signal,
+            terminate_process,
+            terminate_process_tree,
+            suspend_process_tree,
+            resume_process_tree,
             get_open_ports,
             get_startup_apps,
             toggle_startup_app,
             set_process_priority,
             get_process_affinity,
             set_process_affinity,
+            get_process_scheduling,
+            set_process_scheduling,
+            get_process_io_priority,
+            set_process_io_priority,
             get_current_view,
             get_notifications_enabled,
-            set_notifications_enabled
+            set_notifications_enabled,
+            get_history,
+            get_history_range,
+            get_alert_rules,
+            save_alert_rule,
+            delete_alert_rule,
+            get_alert_history,
+            clear_alert_history,
+            get_exporter_config,
+            set_exporter_config,
+            get_sampling_interval,
+            set_sampling_interval,
+            get_stats_snapshot,
+            subscribe_process_view
         ])
         .manage(AppLifecycle {
             is_quitting: AtomicBool::new(false),
@@ -49,8 +110,25 @@ pub fn run() {
             // Create tray icon
             let _tray = tray::create_tray(app)?;
 
+            // Open history, alert rules and settings before the monitor starts feeding them.
+            // Never a fixture here: the GUI's process actions take their PIDs from the samples.
+            let services = monitor::MonitorServices::open(
+                &app.path().app_data_dir()?,
+                &app.path().app_config_dir()?,
+                Box::new(source::LiveSource::new()),
+            )?;
+            app.manage(services.monitor.clone());
+            app.manage(services.waker.clone());
+            app.manage(services.config.clone());
+            app.manage(services.history.clone());
+            app.manage(services.alerts.clone());
+            app.manage(services.exporter.clone());
+
+            let stream = monitor::stream::SharedStatsStream::default();
+            app.manage(stream.clone());
+
             // Start system monitoring in background
-            monitor::start_monitoring(app.handle().clone());
+            monitor::gui::start_monitoring(app.handle().clone(), services, stream);
 
             // Check for --minimized argument
             #[cfg(desktop)]
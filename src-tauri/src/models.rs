@@ -7,23 +7,39 @@ pub struct StartupApp {
     pub enabled: bool,
 }
 
+/// Finer memory figures than RSS (`memory_usage`), which counts shared pages in full
+/// for every process mapping them. Fields the platform can't provide are None.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub struct MemoryBreakdown {
+    pub uss: Option<u64>,    // Unique: private pages, freed if the process exits
+    pub pss: Option<u64>,    // Proportional: private pages plus a fair share of shared ones
+    pub shared: Option<u64>, // Resident pages also mapped by other processes
+    pub swap: Option<u64>,   // Swapped out
+}
+
 #[derive(Serialize, Clone)]
 pub struct ProcessInfo {
     pub pid: u32,
     pub name: String,
+    pub start_time: u64,          // Unix timestamp in seconds, 0 when unknown
+    pub start_ticks: Option<u64>, // With `pid`, identifies the process (see `crate::identity`)
+    pub user: String,             // Empty when unknown
+    pub exe: String,              // Empty when unknown
     pub cpu_usage: f32,
     pub total_cpu_usage: f32,
     pub memory_usage: u64,
     pub total_memory_usage: u64,
+    pub memory_breakdown: Option<MemoryBreakdown>, // Only read on request (see `ProcessView`)
     pub disk_read: u64,
     pub disk_write: u64,
     pub total_disk_read: u64,
     pub total_disk_write: u64,
     pub thread_count: u64,
+    pub suspended: bool,
     pub children: Vec<ProcessInfo>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ComponentInfo {
     pub label: String,
     pub temperature: f32,
@@ -31,10 +47,14 @@ pub struct ComponentInfo {
     pub critical_temperature: Option<f32>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Default)]
 pub struct SystemStats {
+    pub timestamp: u64,   // Unix timestamp in milliseconds when the sample was taken
+    pub interval_ms: u64, // Measured time since the previous sample; rates are per second over it
     pub cpu_usage: f32,
+    pub cpu_cores: Vec<f32>, // Per-core usage, indexed by core
     pub process_count: usize,
+    pub thread_count: u64,
     pub memory_used: u64,
     pub memory_total: u64,
     pub network_up: u64,
@@ -46,34 +66,456 @@ pub struct SystemStats {
     pub top_processes: Vec<ProcessInfo>,
 }
 
+/// One line of the streamed process view
+#[derive(Serialize, Clone, PartialEq)]
+pub struct ProcessRow {
+    pub pid: u32,
+    pub parent: Option<u32>, // Parent in the tree (None for roots, including PID-reuse orphans)
+    pub depth: usize,        // Indentation in tree mode, 0 in flat mode
+    pub child_count: usize,
+    pub name: String,
+    pub start_time: u64,
+    pub start_ticks: Option<u64>,
+    pub cpu_usage: f32,
+    pub total_cpu_usage: f32,
+    pub memory_usage: u64,
+    pub total_memory_usage: u64,
+    pub memory_breakdown: Option<MemoryBreakdown>,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub total_disk_read: u64,
+    pub total_disk_write: u64,
+    pub thread_count: u64,
+    pub suspended: bool,
+}
+
+/// Fields of a `ProcessRow` that differ from the previous frame; absent fields are unchanged
+#[derive(Serialize, Clone)]
+pub struct ProcessChange {
+    pub pid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ticks: Option<Option<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_usage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_cpu_usage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_usage: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_memory_usage: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_breakdown: Option<Option<MemoryBreakdown>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_read: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_write: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_disk_read: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_disk_write: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspended: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsFrameKind {
+    Snapshot, // Replaces the whole view: `added` lists every row
+    Delta,    // Applies on top of the frame with `seq - 1`
+}
+
+/// Payload of `stats-update`. Rows cover only the subscribed view's window.
+/// A client that sees a gap in `seq` asks for a snapshot.
+#[derive(Serialize, Clone)]
+pub struct StatsFrame {
+    pub seq: u64,
+    pub kind: StatsFrameKind,
+    pub stats: SystemStats, // Global readings; `top_processes` is left empty
+    pub total_rows: usize,  // Rows in the whole view, before the window is applied
+    pub added: Vec<ProcessRow>,
+    pub removed: Vec<u32>,
+    pub changed: Vec<ProcessChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<u32>>, // Display order of the window's PIDs, when it changed
+}
+
+/// What processes are grouped by, as an alternative to the tree
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Executable,  // Executable path
+    User,        // Owning user
+    Cgroup,      // Control group, named after its systemd unit (Linux)
+    Application, // Installed desktop application, matched via `.desktop` entries (Linux)
+}
+
+/// Processes sharing an executable, user, cgroup or application, with their summed usage
+#[derive(Serialize, Clone)]
+pub struct ProcessGroup {
+    pub key: String, // Stable identity: path, login name, cgroup path or desktop entry ID
+    pub name: String, // Display name
+    pub process_count: usize,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub thread_count: u64,
+    pub processes: Vec<ProcessInfo>, // Members without children, own values only
+}
+
 #[derive(Serialize, Clone)]
 pub struct ProcessDetails {
     pub pid: u32,
+    pub start_ticks: Option<u64>,
     pub name: String,
     pub cmd: Vec<String>,
     pub exe: String,
+    pub user: String,
     pub cwd: String,
     pub root: String,
     pub status: String,
     pub run_time: u64,
     pub memory_usage: u64,
+    pub memory_breakdown: Option<MemoryBreakdown>,
     pub cpu_usage: f32,
     pub environ: Vec<String>,
+    pub scheduling: Option<Scheduling>, // Linux only
+    pub io_priority: Option<IoPriority>,
 }
 
-#[derive(Serialize, Clone)]
+/// Linux scheduling policy (`SCHED_*`)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedPolicy {
+    Other,
+    Batch,
+    Idle,
+    Fifo,
+    Rr,
+    Deadline,
+}
+
+/// CPU budget of a `SCHED_DEADLINE` process, in nanoseconds:
+/// `runtime` in every `period`, finished within `deadline` of the period's start
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct DeadlineParams {
+    pub runtime: u64,
+    pub deadline: u64,
+    pub period: u64,
+}
+
+/// How the kernel schedules a process. `nice` (-20 to 19) applies to Other and
+/// Batch, `rt_priority` (1 to 99) to Fifo and Rr, `deadline` to Deadline.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Scheduling {
+    pub policy: SchedPolicy,
+    pub nice: i32,
+    pub rt_priority: u32,
+    pub deadline: Option<DeadlineParams>,
+}
+
+/// I/O scheduling class, as with `ionice -c`
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IoClass {
+    /// Not set: follows the CPU nice value
+    None,
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/// I/O priority of a process; `level` 0 (highest) to 7 applies to Realtime and
+/// BestEffort. On Windows the classes map to I/O priority hints: Idle is Very Low,
+/// BestEffort 5 to 7 is Low, 0 to 4 (and None) Normal, Realtime High.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct IoPriority {
+    pub class: IoClass,
+    pub level: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PortInfo {
     pub pid: Option<u32>,
+    pub start_ticks: Option<u64>, // Of the owning process, for commands acting on it
     pub process_name: String,
     pub port: u16,
     pub protocol: String, // "TCP" or "UDP"
     pub address: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SensorReading {
+    pub label: String,
+    pub temperature: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistorySample {
+    pub timestamp: u64, // Unix timestamp in milliseconds
+    pub cpu_usage: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub network_up: u64,
+    pub network_down: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub sensors: Vec<SensorReading>,
+}
+
 #[derive(Serialize, Clone)]
-pub struct ModuleInfo {
+pub struct HistoryRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertMetric {
+    GlobalCpu,
+    CoreCpu {
+        core: usize,
+    },
+    MemoryPercent,
+    MemoryUsed,
+    NetworkUp,
+    NetworkDown,
+    DiskRead,
+    DiskWrite,
+    // Per-process metrics; `name` restricts the rule to processes with that name,
+    // `query` to processes matching a query expression (see `crate::query`)
+    ProcessCpu {
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>,
+    },
+    ProcessMemory {
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>,
+    },
+    ProcessMemoryPercent {
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>,
+    },
+    SensorTemperature {
+        label: String,
+    },
+    // 1 while something listens on the port, 0 otherwise
+    PortListening {
+        port: u16,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertAction {
+    // Desktop notification (respects the notifications setting)
+    Notify,
+    // Run a program; `{pid}`, `{name}`, `{value}`, ... placeholders in args are expanded
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    // POST the alert as JSON
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: std::collections::HashMap<String, String>,
+    },
+    // Append the alert as a JSON line
+    LogFile {
+        path: String,
+    },
+    Syslog,
+}
+
+fn default_alert_actions() -> Vec<AlertAction> {
+    vec![AlertAction::Notify]
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlertRule {
+    pub id: String,
     pub name: String,
-    pub path: String,
+    pub enabled: bool,
+    pub metric: AlertMetric,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    pub duration_secs: u64, // How long the condition must hold before firing
+    pub hysteresis: f64,    // Distance back past the threshold needed to clear
+    pub cooldown_secs: u64, // Minimum time between two firings for the same subject
+    pub severity: AlertSeverity,
+    #[serde(default = "default_alert_actions")]
+    pub actions: Vec<AlertAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlertEvent {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub severity: AlertSeverity,
+    pub timestamp: u64, // Unix timestamp in milliseconds
+    pub subject: String,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    pub value: f64,
+    pub threshold: f64,
+    pub message: String,
+}
+
+/// A signal `send_signal` can deliver. On Windows only `Term` (close request),
+/// `Int` (Ctrl+C) and `Kill` have an equivalent.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSignal {
+    Term,
+    Kill,
+    Hup,
+    Int,
+    Usr1,
+    Usr2,
+    Stop,
+    Cont,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminateStage {
+    Requested, // Asked to exit, grace period started
+    Waiting,   // Still running within the grace period
+    Killing,   // Grace period over, force-killed
+    Exited,    // Exited on its own
+    Killed,    // Gone after being force-killed
+}
+
+/// Progress of a graceful termination, emitted as `terminate-progress` while it
+/// runs; the final one (`Exited` or `Killed`) is also the command's result
+#[derive(Serialize, Clone, Debug)]
+pub struct TerminateProgress {
+    pub pid: u32,
+    pub stage: TerminateStage,
+    pub elapsed_ms: u64,
+    pub grace_ms: u64,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeActionStatus {
+    Done,   // Suspended, resumed, or exited when asked to
+    Killed, // Force-killed after the grace period
+    Gone,   // Exited, or its PID was reused, before it was reached
+    Failed,
+}
+
+/// What an action on a whole process tree did to one of its processes
+#[derive(Serialize, Clone, Debug)]
+pub struct TreeActionResult {
+    pub pid: u32,
+    pub name: String,
+    pub status: TreeActionStatus,
+    pub error: Option<crate::error::CommandError>,
+}
+
+/// One mapped region of a process's address space (a line of `/proc/<pid>/smaps`)
+#[derive(Serialize, Clone)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    pub perms: String,  // e.g. `r-xp`: read, write, execute, private/shared
+    pub offset: u64,    // Into the mapped file
+    pub device: String, // `major:minor` of the file's device
+    pub inode: u64,
+    pub path: String, // File, a pseudo-path such as `[heap]` or `[stack]`, or empty for anonymous memory
+    pub size: u64,
+    pub rss: u64,
+    pub pss: u64,
+    pub dirty: u64, // Shared and private dirty pages
+    pub swap: u64,
+}
+
+/// Regions of one mapping added up, e.g. every segment of `libc.so.6`
+#[derive(Serialize, Clone)]
+pub struct MappingSummary {
+    pub path: String, // As in `MemoryRegion`; `[anon]` for anonymous memory
+    pub regions: usize,
+    pub size: u64,
+    pub rss: u64,
+    pub pss: u64,
+    pub dirty: u64,
+    pub swap: u64,
+}
+
+/// A process's address space, region by region and summed per mapping (largest RSS first)
+#[derive(Serialize, Clone)]
+pub struct MemoryMap {
+    pub pid: u32,
+    pub regions: Vec<MemoryRegion>,
+    pub summary: Vec<MappingSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ExporterConfig {
+    pub enabled: bool,
+    pub address: String, // host:port the /metrics endpoint listens on
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:9187".to_string(),
+        }
+    }
+}
+
+pub const MIN_SAMPLING_INTERVAL_MS: u64 = 250;
+pub const MAX_SAMPLING_INTERVAL_MS: u64 = 60_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AppConfig {
+    pub exporter: ExporterConfig,
+    pub sampling_interval_ms: u64, // Time between monitor samples
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            exporter: ExporterConfig::default(),
+            sampling_interval_ms: 1000,
+        }
+    }
 }
 
 // Global Lifecycle State
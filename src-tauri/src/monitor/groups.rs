@@ -0,0 +1,385 @@
+//! Alternative groupings of the process list: by executable, owning user,
+//! systemd unit (cgroup) or desktop application. Unlike the tree, a group collects
+//! processes wherever they sit, so a browser's or an Electron app's helpers end up
+//! together even when they were re-parented or spawned through a zygote.
+
+use super::process_node;
+use crate::models::{GroupBy, ProcessGroup, ProcessInfo};
+use crate::source::{ProcessRecord, SystemSource};
+use std::collections::HashMap;
+use std::path::Path;
+
+// How far up the parent chain an unmatched process looks for an application
+const MAX_APP_DEPTH: usize = 16;
+
+/// Group the processes of the last sample. Groups come heaviest (CPU, then memory) first,
+/// and so do their members.
+pub fn group(
+    records: &[ProcessRecord],
+    cpu_count: f32,
+    by: GroupBy,
+    source: &dyn SystemSource,
+) -> Vec<ProcessGroup> {
+    let apps = match by {
+        GroupBy::Application => apps::index(),
+        _ => Default::default(),
+    };
+    let parents: HashMap<u32, &ProcessRecord> = records.iter().map(|r| (r.pid, r)).collect();
+    let mut matched = HashMap::new();
+
+    let mut groups: HashMap<String, ProcessGroup> = HashMap::new();
+    for record in records {
+        let (key, name) = match by {
+            GroupBy::Executable => executable(record),
+            GroupBy::User => match record.user.as_str() {
+                "" => (String::new(), "Unknown user".to_string()),
+                user => (user.to_string(), user.to_string()),
+            },
+            GroupBy::Cgroup => match source.cgroup(record.pid) {
+                Some(path) => (path.clone(), unit_name(&path).to_string()),
+                None => (String::new(), "No cgroup".to_string()),
+            },
+            GroupBy::Application => match application(record, &parents, &apps, &mut matched) {
+                Some(app) => (apps[app].id.clone(), apps[app].name.clone()),
+                None => (String::new(), "Other processes".to_string()),
+            },
+        };
+
+        let process = process_node(record, cpu_count);
+        let group = groups.entry(key.clone()).or_insert_with(|| ProcessGroup {
+            key,
+            name,
+            process_count: 0,
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            disk_read: 0,
+            disk_write: 0,
+            thread_count: 0,
+            processes: Vec::new(),
+        });
+        group.process_count += 1;
+        group.cpu_usage += process.cpu_usage;
+        group.memory_usage += process.memory_usage;
+        group.disk_read += process.disk_read;
+        group.disk_write += process.disk_write;
+        group.thread_count += process.thread_count;
+        group.processes.push(process);
+    }
+
+    let heaviest = |cpu_a: f32, mem_a: u64, cpu_b: f32, mem_b: u64| {
+        cpu_b
+            .partial_cmp(&cpu_a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(mem_b.cmp(&mem_a))
+    };
+    let mut groups: Vec<ProcessGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group.processes.sort_by(|a: &ProcessInfo, b: &ProcessInfo| {
+            heaviest(a.cpu_usage, a.memory_usage, b.cpu_usage, b.memory_usage)
+        });
+    }
+    groups.sort_by(|a, b| heaviest(a.cpu_usage, a.memory_usage, b.cpu_usage, b.memory_usage));
+    groups
+}
+
+/// Executable path, falling back to the process name for kernel threads and
+/// processes whose path can't be read
+fn executable(record: &ProcessRecord) -> (String, String) {
+    if record.exe.is_empty() {
+        return (format!("[{}]", record.name), record.name.clone());
+    }
+    let name = Path::new(&record.exe)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| record.exe.clone());
+    (record.exe.clone(), name)
+}
+
+/// Last component of a cgroup path that names a systemd unit, e.g. `nginx.service`
+/// for `/system.slice/nginx.service`; the full path if none does
+fn unit_name(path: &str) -> &str {
+    path.rsplit('/')
+        .find(|part| {
+            [".service", ".scope", ".slice"]
+                .iter()
+                .any(|suffix| part.ends_with(suffix))
+        })
+        .unwrap_or(path)
+}
+
+/// The application a process belongs to: its own match, else the nearest ancestor's,
+/// so helpers launched under another name (renderers, crash handlers) join their app.
+/// `matched` remembers the answer for every process looked at on the way.
+fn application(
+    record: &ProcessRecord,
+    parents: &HashMap<u32, &ProcessRecord>,
+    apps: &[apps::App],
+    matched: &mut HashMap<u32, Option<usize>>,
+) -> Option<usize> {
+    let mut chain = Vec::new();
+    let mut current = Some(record);
+    let mut found = None;
+    while let Some(process) = current.filter(|_| chain.len() < MAX_APP_DEPTH) {
+        if let Some(&known) = matched.get(&process.pid) {
+            found = known;
+            break;
+        }
+        chain.push(process.pid);
+        found = apps.iter().position(|app| app.matches(process));
+        if found.is_some() {
+            break;
+        }
+        current = process.parent.and_then(|pid| parents.get(&pid).copied());
+    }
+    for pid in chain {
+        matched.insert(pid, found);
+    }
+    found
+}
+
+mod apps {
+    use crate::source::ProcessRecord;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Installed applications are re-read at most this often
+    const INDEX_TTL: Duration = Duration::from_secs(60);
+
+    static INDEX: Mutex<Option<(Instant, Arc<Vec<App>>)>> = Mutex::new(None);
+
+    /// An installed application a process can be attributed to
+    pub struct App {
+        pub id: String,
+        pub name: String,
+        exe: Option<PathBuf>,     // Resolved binary the launcher runs
+        exe_name: Option<String>, // Its file name
+        exe_name_unique: bool,    // No other entry runs a program of that name
+        wm_class: Option<String>, // Lowercase; usually the process name of Electron apps
+    }
+
+    impl App {
+        pub fn matches(&self, process: &ProcessRecord) -> bool {
+            let exe = Path::new(&process.exe);
+            if !process.exe.is_empty() && self.exe.as_deref() == Some(exe) {
+                return true;
+            }
+            // The file name alone only counts without paths to compare, and only when
+            // one entry runs it; otherwise an entry for some python3 or electron
+            // program would take every process of that name
+            let no_path = process.exe.is_empty() || self.exe.is_none();
+            if let Some(own) = self.exe_name.as_ref().filter(|_| self.exe_name_unique) {
+                let exe_name = exe.file_name().and_then(|n| n.to_str());
+                if no_path && (exe_name == Some(own.as_str()) || process.name == *own) {
+                    return true;
+                }
+            }
+            self.wm_class
+                .as_ref()
+                .is_some_and(|class| process.name.to_lowercase() == *class)
+        }
+    }
+
+    /// Installed applications, cached for `INDEX_TTL`
+    pub fn index() -> Arc<Vec<App>> {
+        let mut cached = INDEX.lock().unwrap();
+        if let Some((read_at, apps)) = cached.as_ref() {
+            if read_at.elapsed() < INDEX_TTL {
+                return apps.clone();
+            }
+        }
+        let apps = Arc::new(scan());
+        *cached = Some((Instant::now(), apps.clone()));
+        apps
+    }
+
+    /// `.desktop` entries in the XDG data directories; the first one with a given ID wins
+    #[cfg(target_os = "linux")]
+    fn scan() -> Vec<App> {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|h| h.join(".local/share")));
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+        let mut dirs: Vec<PathBuf> = data_home.into_iter().collect();
+        dirs.extend(std::env::split_paths(&data_dirs));
+        dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+        dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+
+        let mut apps: Vec<App> = Vec::new();
+        for dir in dirs {
+            let mut files = Vec::new();
+            desktop_files(&dir.join("applications"), &mut files, 0);
+            for path in files {
+                let Some(app) = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|text| parse(&path, &text))
+                else {
+                    continue;
+                };
+                if !apps.iter().any(|a| a.id == app.id) {
+                    apps.push(app);
+                }
+            }
+        }
+
+        for i in 0..apps.len() {
+            let name = apps[i].exe_name.as_ref();
+            let shared = apps.iter().filter(|a| a.exe_name.as_ref() == name).count() > 1;
+            apps[i].exe_name_unique = !shared;
+        }
+        apps
+    }
+
+    /// Elsewhere applications are known by their bundle or executable,
+    /// which the `Executable` grouping already covers
+    #[cfg(not(target_os = "linux"))]
+    fn scan() -> Vec<App> {
+        Vec::new()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn desktop_files(dir: &Path, out: &mut Vec<PathBuf>, depth: usize) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() && depth < 2 {
+                desktop_files(&path, out, depth + 1);
+            } else if path.extension().is_some_and(|e| e == "desktop") {
+                out.push(path);
+            }
+        }
+    }
+
+    /// The `[Desktop Entry]` group of a visible application entry
+    #[cfg(target_os = "linux")]
+    fn parse(path: &Path, text: &str) -> Option<App> {
+        let mut in_entry = false;
+        let (mut name, mut exec, mut try_exec, mut wm_class) = (None, None, None, None);
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=').filter(|_| in_entry) else {
+                continue;
+            };
+            match key.trim() {
+                "Type" if value.trim() != "Application" => return None,
+                "Hidden" | "NoDisplay" if value.trim() == "true" => return None,
+                "Name" => name = Some(value.trim().to_string()),
+                "Exec" => exec = Some(value.trim().to_string()),
+                "TryExec" => try_exec = Some(value.trim().to_string()),
+                "StartupWMClass" => wm_class = Some(value.trim().to_lowercase()),
+                _ => {}
+            }
+        }
+
+        let program = try_exec.or_else(|| exec.as_deref().and_then(program));
+        let exe = program.as_deref().and_then(resolve);
+        let exe_name = program
+            .as_deref()
+            .and_then(|p| Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().to_string());
+        Some(App {
+            id: path.file_stem()?.to_string_lossy().to_string(),
+            name: name?,
+            exe,
+            exe_name,
+            exe_name_unique: true,
+            wm_class,
+        })
+    }
+
+    /// The program an `Exec` line starts, skipping `env VAR=value` prefixes. Launchers
+    /// such as `flatpak run` name no useful binary; those entries match by window class.
+    #[cfg(target_os = "linux")]
+    fn program(exec: &str) -> Option<String> {
+        let mut words = exec
+            .split_whitespace()
+            .map(|word| word.trim_matches('"'))
+            .skip_while(|word| *word == "env" || word.contains('='));
+        let program = words.next()?;
+        let launcher = matches!(
+            Path::new(program).file_name().and_then(|n| n.to_str()),
+            Some("flatpak" | "snap" | "sh" | "bash" | "gio" | "xdg-open")
+        );
+        (!launcher).then(|| program.to_string())
+    }
+
+    /// Absolute path of a program, looked up in `PATH` and with symlinks resolved,
+    /// since `/usr/bin/code` is usually a link into the app's own directory
+    #[cfg(target_os = "linux")]
+    fn resolve(program: &str) -> Option<PathBuf> {
+        let path = Path::new(program);
+        let found = if path.is_absolute() {
+            Some(path.to_path_buf())
+        } else {
+            std::env::var_os("PATH").and_then(|paths| {
+                std::env::split_paths(&paths)
+                    .map(|dir| dir.join(program))
+                    .find(|candidate| candidate.is_file())
+            })
+        }?;
+        Some(std::fs::canonicalize(&found).unwrap_or(found))
+    }
+
+    #[cfg(all(test, target_os = "linux"))]
+    mod tests {
+        use super::*;
+
+        fn process(name: &str, exe: &str) -> ProcessRecord {
+            ProcessRecord {
+                pid: 1,
+                parent: None,
+                name: name.into(),
+                start_time: 0,
+                start_ticks: None,
+                cpu_usage: 0.0,
+                memory: 0,
+                memory_breakdown: None,
+                disk_read: 0,
+                disk_write: 0,
+                thread_count: 1,
+                user: String::new(),
+                exe: exe.into(),
+                suspended: false,
+            }
+        }
+
+        fn entry(id: &str, body: &str) -> Option<App> {
+            let text = format!("[Desktop Entry]\nType=Application\nName={}\n{}", id, body);
+            parse(
+                Path::new(&format!("/usr/share/applications/{}.desktop", id)),
+                &text,
+            )
+        }
+
+        #[test]
+        fn hidden_entries_are_skipped() {
+            assert!(entry("shown", "Exec=/opt/shown/run").is_some());
+            assert!(entry("settings-panel", "Exec=/opt/panel\nNoDisplay=true").is_none());
+            assert!(entry("removed", "Exec=/opt/removed\nHidden=true").is_none());
+        }
+
+        #[test]
+        fn file_names_only_match_without_paths() {
+            let idle = entry("idle", "Exec=/opt/idle/python3 -m idlelib").unwrap();
+            assert!(idle.matches(&process("python3", "/opt/idle/python3")));
+            assert!(!idle.matches(&process("python3", "/usr/bin/python3")));
+            assert!(idle.matches(&process("python3", "")));
+
+            let mut shared = entry("idle", "Exec=/opt/idle/python3 -m idlelib").unwrap();
+            shared.exe_name_unique = false;
+            assert!(!shared.matches(&process("python3", "")));
+            assert!(shared.matches(&process("python3", "/opt/idle/python3")));
+        }
+    }
+}
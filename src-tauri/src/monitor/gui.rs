@@ -0,0 +1,91 @@
+use super::stream::SharedStatsStream;
+use super::{MonitorServices, MonitorSink};
+use crate::alerts::actions::Notifier;
+use crate::models::{AlertEvent, AppLifecycle, SystemStats};
+use tauri::{path::BaseDirectory, AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+/// Forwards monitor output to the webview and shows desktop notifications
+struct AppSink<R: Runtime> {
+    app_handle: AppHandle<R>,
+    icon_path: Option<String>,
+    stream: SharedStatsStream,
+}
+
+impl<R: Runtime> MonitorSink for AppSink<R> {
+    fn stats(&self, stats: &SystemStats) {
+        // Emit only what changed since the previous tick; the webview fetches a
+        // snapshot on load and whenever it misses a frame
+        let frame = self.stream.lock().unwrap().advance(stats);
+        if let Err(e) = self.app_handle.emit("stats-update", frame) {
+            eprintln!("Error emitting stats: {}", e);
+        }
+    }
+
+    fn alert(&self, event: &AlertEvent) {
+        if let Err(e) = self.app_handle.emit("alert-fired", event) {
+            eprintln!("Error emitting alert: {}", e);
+        }
+    }
+
+    fn notifier(&self) -> Option<&dyn Notifier> {
+        Some(self)
+    }
+
+    // Hide, close and --minimized destroy the window, so its absence means nobody listens
+    fn active(&self) -> bool {
+        self.app_handle.get_webview_window("main").is_some()
+    }
+
+    fn memory_breakdown(&self) -> bool {
+        self.stream.lock().unwrap().view().wants_memory_breakdown()
+    }
+}
+
+impl<R: Runtime> Notifier for AppSink<R> {
+    fn notify(&self, event: &AlertEvent) {
+        let enabled = self
+            .app_handle
+            .try_state::<AppLifecycle>()
+            .map(|state| {
+                state
+                    .notifications_enabled
+                    .load(std::sync::atomic::Ordering::Relaxed)
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return;
+        }
+
+        let mut notification = self
+            .app_handle
+            .notification()
+            .builder()
+            .title(&event.rule_name)
+            .body(&event.message);
+
+        if let Some(icon) = &self.icon_path {
+            notification = notification.icon(icon);
+        }
+
+        let _ = notification.show();
+    }
+}
+
+pub fn start_monitoring<R: Runtime>(
+    app_handle: AppHandle<R>,
+    services: MonitorServices,
+    stream: SharedStatsStream,
+) {
+    let sink = AppSink {
+        stream,
+        icon_path: app_handle
+            .path()
+            .resolve("icons/icon.png", BaseDirectory::Resource)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string()),
+        app_handle,
+    };
+
+    tauri::async_runtime::spawn(super::run(services, sink));
+}
@@ -0,0 +1,675 @@
+pub mod groups;
+pub mod gui;
+pub mod stream;
+pub mod view;
+
+use crate::alerts::actions::{self, Notifier};
+use crate::alerts::{self, AlertEvaluator, AlertStore};
+use crate::config::ConfigStore;
+use crate::error::{CommandError, CommandResult};
+use crate::exporter::MetricsExporter;
+use crate::history::{self, HistoryStore};
+use crate::models::{
+    AlertEvent, GroupBy, PortInfo, ProcessDetails, ProcessGroup, ProcessInfo, SystemStats,
+    MAX_SAMPLING_INTERVAL_MS, MIN_SAMPLING_INTERVAL_MS,
+};
+use crate::source::{self, ProcessRecord, SystemSource};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Receives the output of the monitor loop. The GUI forwards it to the webview;
+/// the headless daemon only needs the side effects (history, alerts, exporter).
+pub trait MonitorSink: Send {
+    fn stats(&self, _stats: &SystemStats) {}
+
+    fn alert(&self, _event: &AlertEvent) {}
+
+    /// Target for `Notify` alert actions, if desktop notifications are available
+    fn notifier(&self) -> Option<&dyn Notifier> {
+        None
+    }
+
+    /// Whether anyone is looking at the full stats stream. While this is false the
+    /// loop only feeds alerts, history and the exporter, without building the tree,
+    /// at no more than one sample per `IDLE_INTERVAL`.
+    fn active(&self) -> bool {
+        true
+    }
+
+    /// Whether the next active sample should include each process's `MemoryBreakdown`
+    fn memory_breakdown(&self) -> bool {
+        false
+    }
+}
+
+/// Slowest sampling rate used while the sink is inactive
+pub const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Cuts the monitor's current sleep short, e.g. when a window opens while it idles
+#[derive(Default)]
+pub struct MonitorWaker(Notify);
+
+impl MonitorWaker {
+    pub fn wake(&self) {
+        self.0.notify_one();
+    }
+}
+
+/// The sampling core, shared so commands can read the latest sample instead of
+/// building their own `System`
+pub type SharedMonitor = Arc<Mutex<Monitor>>;
+
+/// Subsystems fed by every sample, shared with whoever serves queries on them
+#[derive(Clone)]
+pub struct MonitorServices {
+    pub monitor: SharedMonitor,
+    pub waker: Arc<MonitorWaker>,
+    pub config: Arc<ConfigStore>,
+    pub history: Arc<HistoryStore>,
+    pub alerts: Arc<AlertStore>,
+    pub exporter: Arc<MetricsExporter>,
+}
+
+impl MonitorServices {
+    /// Open the stores under the given directories and start the metrics endpoint if
+    /// enabled; samples are taken from `source`
+    pub fn open(
+        data_dir: &Path,
+        config_dir: &Path,
+        source: Box<dyn SystemSource>,
+    ) -> io::Result<Self> {
+        let config = ConfigStore::load(config_dir.join("config.json"))?;
+
+        // Metrics endpoint is off by default; a bad saved address shouldn't prevent startup
+        let exporter = MetricsExporter::default();
+        if let Err(e) = exporter.apply(&config.get().exporter) {
+            eprintln!("{}", e);
+        }
+
+        Ok(Self {
+            monitor: Arc::new(Mutex::new(Monitor::with_source(source))),
+            waker: Arc::new(MonitorWaker::default()),
+            config: Arc::new(config),
+            // Defaults mirror the old built-in watchdog until the user saves their own rules
+            alerts: Arc::new(AlertStore::load(config_dir.join("alert_rules.json"))?),
+            history: Arc::new(HistoryStore::new(data_dir.join("history"))?),
+            exporter: Arc::new(exporter),
+        })
+    }
+}
+
+/// Sampling core: reads a `SystemSource` and turns each refresh into a `SystemStats`
+pub struct Monitor {
+    source: Box<dyn SystemSource>,
+    // When the source was last refreshed; byte counters are divided by the time since
+    last_refresh: Instant,
+    // Processes of the last sample (rates already applied), for groupings
+    records: Vec<ProcessRecord>,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    /// Monitor the live system (or the fixture named by `ACTIOWATCH_FIXTURE`)
+    pub fn new() -> Self {
+        Self::with_source(source::from_env())
+    }
+
+    pub fn with_source(source: Box<dyn SystemSource>) -> Self {
+        // Sources take their first reading on creation
+        Self {
+            source,
+            last_refresh: Instant::now(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Refresh all sources and build a new sample
+    pub fn sample(&mut self) -> SystemStats {
+        self.take_sample(true)
+    }
+
+    /// Like `sample`, but `top_processes` is a flat, unsorted list without children.
+    /// Used while nobody is looking at the tree.
+    pub fn sample_flat(&mut self) -> SystemStats {
+        self.take_sample(false)
+    }
+
+    fn take_sample(&mut self, tree: bool) -> SystemStats {
+        self.source.refresh();
+        let now = Instant::now();
+        let timestamp = history::now_ms();
+
+        // Turn byte counts since the last refresh into per-second rates over the
+        // measured interval, so a late or slow tick doesn't inflate them
+        let interval = self
+            .source
+            .interval()
+            .unwrap_or_else(|| now.duration_since(self.last_refresh));
+        self.last_refresh = now;
+        let seconds = interval.as_secs_f64().max(0.001);
+        let rate = |bytes: u64| (bytes as f64 / seconds).round() as u64;
+
+        let host = self.source.host();
+        let mut records = self.source.processes();
+        for record in &mut records {
+            record.disk_read = rate(record.disk_read);
+            record.disk_write = rate(record.disk_write);
+        }
+
+        // Calculate global Network usage
+        // The source reports bytes transmitted/received SINCE THE LAST REFRESH.
+        let network = self.source.network();
+
+        // Guard against an empty core list (fixtures) to keep the division finite
+        let cpu_count = host.cpu_cores.len().max(1) as f32;
+
+        let processes = if tree {
+            build_tree(&records, cpu_count)
+        } else {
+            records.iter().map(|r| process_node(r, cpu_count)).collect()
+        };
+
+        // Aggregate global disk usage from processes
+        let mut total_disk_read = 0;
+        let mut total_disk_write = 0;
+        for p in &records {
+            total_disk_read += p.disk_read;
+            total_disk_write += p.disk_write;
+        }
+
+        let stats = SystemStats {
+            timestamp,
+            interval_ms: interval.as_millis() as u64,
+            cpu_usage: host.cpu_usage,
+            cpu_cores: host.cpu_cores,
+            process_count: records.len(),
+            thread_count: records.iter().map(|r| r.thread_count).sum(),
+            memory_used: host.memory_used,
+            memory_total: host.memory_total,
+            network_up: rate(network.transmitted),
+            network_down: rate(network.received),
+            disk_read: total_disk_read,
+            disk_write: total_disk_write,
+            gpu_usage: None,
+            components: self.source.components(),
+            top_processes: processes,
+        };
+        self.records = records;
+        stats
+    }
+
+    /// Listening ports as reported by the source
+    pub fn ports(&self) -> CommandResult<Vec<PortInfo>> {
+        self.source.ports()
+    }
+
+    /// Read per-process memory breakdowns from the next refresh on (see `SystemSource`)
+    pub fn set_memory_breakdown(&mut self, enabled: bool) {
+        self.source.set_memory_breakdown(enabled);
+    }
+
+    /// The processes of the last sample grouped by `by` (see `groups`)
+    pub fn process_groups(&self, by: GroupBy) -> Vec<ProcessGroup> {
+        let cpu_count = self.cpu_count().max(1) as f32;
+        groups::group(&self.records, cpu_count, by, self.source.as_ref())
+    }
+
+    /// `pid` and all of its descendants in the last sample, every parent before its
+    /// children; empty if `pid` wasn't in it
+    pub fn subtree(&self, pid: u32) -> Vec<ProcessRecord> {
+        let by_pid: HashMap<u32, &ProcessRecord> =
+            self.records.iter().map(|r| (r.pid, r)).collect();
+        let children_map = children_map(&self.records, &by_pid);
+
+        let mut subtree = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![pid];
+        while let Some(pid) = stack.pop() {
+            let Some(process) = by_pid.get(&pid).filter(|_| seen.insert(pid)) else {
+                continue;
+            };
+            subtree.push((*process).clone());
+            if let Some(children) = children_map.get(&pid) {
+                stack.extend(children);
+            }
+        }
+        subtree
+    }
+
+    /// Whether the samples are replayed from a recording (see `SystemSource::is_replay`)
+    pub fn is_replay(&self) -> bool {
+        self.source.is_replay()
+    }
+
+    pub fn cpu_count(&self) -> usize {
+        self.source.host().cpu_cores.len()
+    }
+
+    /// Refresh one process and return its details; CPU usage comes from the latest sample
+    pub fn process_details(&mut self, pid: u32) -> CommandResult<ProcessDetails> {
+        if !self.source.refresh_process(pid) {
+            return Err(CommandError::process_not_found("get_process_details", pid));
+        }
+        self.source
+            .process_details(pid)
+            .ok_or_else(|| CommandError::process_not_found("get_process_details", pid))
+    }
+}
+
+/// A process without children; CPU usage normalized (0-100%) on all platforms
+fn process_node(process: &ProcessRecord, cpu_count: f32) -> ProcessInfo {
+    let cpu_usage = process.cpu_usage / cpu_count;
+
+    ProcessInfo {
+        pid: process.pid,
+        name: process.name.clone(),
+        start_time: process.start_time,
+        start_ticks: process.start_ticks,
+        user: process.user.clone(),
+        exe: process.exe.clone(),
+        cpu_usage,
+        total_cpu_usage: cpu_usage,
+        memory_usage: process.memory,
+        total_memory_usage: process.memory,
+        memory_breakdown: process.memory_breakdown,
+        disk_read: process.disk_read,
+        disk_write: process.disk_write,
+        total_disk_read: process.disk_read,
+        total_disk_write: process.disk_write,
+        thread_count: process.thread_count,
+        suspended: process.suspended,
+        children: Vec::new(),
+    }
+}
+
+/// Subtrees holding more than this share of all processes keep their own values in
+/// `total_*`. Summing everything under init, or under a system process that PID reuse
+/// linked wrongly, is the "giant tree" `build_tree` already guards against; a number
+/// that large says nothing about the branch anyway.
+const GIANT_TREE_SHARE: f64 = 0.5;
+
+/// Fill the `total_*` fields of every node: with `aggregate`, the process plus all
+/// of its descendants (except giant subtrees, see `GIANT_TREE_SHARE`); otherwise the
+/// process's own values. Idempotent, so the same tree can be switched back and forth.
+pub fn set_totals(tree: &mut [ProcessInfo], aggregate: bool) {
+    fn count(nodes: &[ProcessInfo]) -> usize {
+        nodes.iter().map(|n| 1 + count(&n.children)).sum()
+    }
+
+    // Returns the size of the subtree
+    fn add_up(node: &mut ProcessInfo, aggregate: bool, limit: usize) -> usize {
+        node.total_cpu_usage = node.cpu_usage;
+        node.total_memory_usage = node.memory_usage;
+        node.total_disk_read = node.disk_read;
+        node.total_disk_write = node.disk_write;
+
+        let mut size = 1;
+        for child in &mut node.children {
+            size += add_up(child, aggregate, limit);
+        }
+
+        if aggregate && size <= limit {
+            for child in &node.children {
+                node.total_cpu_usage += child.total_cpu_usage;
+                node.total_memory_usage += child.total_memory_usage;
+                node.total_disk_read += child.total_disk_read;
+                node.total_disk_write += child.total_disk_write;
+            }
+        }
+        size
+    }
+
+    let limit = ((count(tree) as f64 * GIANT_TREE_SHARE) as usize).max(1);
+    for node in tree {
+        add_up(node, aggregate, limit);
+    }
+}
+
+/// Map of PID -> children PIDs, leaving out parent links that fail the start-time
+/// check (PID reuse)
+fn children_map(
+    records: &[ProcessRecord],
+    by_pid: &HashMap<u32, &ProcessRecord>,
+) -> HashMap<u32, Vec<u32>> {
+    let mut children_map: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    for process in records {
+        if let Some(parent) = process.parent {
+            let is_valid = match by_pid.get(&parent) {
+                Some(parent_proc) => is_parent(parent_proc, process),
+                // Parent process not in list?
+                // If we can't find parent, we can't aggregate anyway.
+                None => true,
+            };
+
+            if is_valid {
+                children_map.entry(parent).or_default().push(process.pid);
+            }
+        }
+    }
+    children_map
+}
+
+/// Arrange processes by parent, sorted by CPU usage. Parent links that fail the
+/// start-time check (PID reuse) make the child a root instead.
+fn build_tree(records: &[ProcessRecord], cpu_count: f32) -> Vec<ProcessInfo> {
+    let by_pid: HashMap<u32, &ProcessRecord> = records.iter().map(|r| (r.pid, r)).collect();
+    let children_map = children_map(records, &by_pid);
+
+    // Helper to build process node with aggregation
+    fn build_process_node(
+        pid: u32,
+        by_pid: &HashMap<u32, &ProcessRecord>,
+        children_map: &HashMap<u32, Vec<u32>>,
+        cpu_count: f32,
+    ) -> Option<ProcessInfo> {
+        if let Some(process) = by_pid.get(&pid) {
+            let mut node = process_node(process, cpu_count);
+
+            if let Some(children_pids) = children_map.get(&pid) {
+                for &child_pid in children_pids {
+                    if let Some(child_node) =
+                        build_process_node(child_pid, by_pid, children_map, cpu_count)
+                    {
+                        node.children.push(child_node);
+                    }
+                }
+            }
+
+            // Totals start as the process's own values; subtree sums are opt-in
+            // per view (see `set_totals`)
+
+            // Sort children by Total CPU usage
+            node.children.sort_by(|a, b| {
+                b.total_cpu_usage
+                    .partial_cmp(&a.total_cpu_usage)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            Some(node)
+        } else {
+            None
+        }
+    }
+
+    // Identify roots
+    let roots: Vec<u32> = records
+        .iter()
+        .filter_map(|process| {
+            match process.parent {
+                None => Some(process.pid),
+                Some(ppid) => {
+                    // Check link validity - mirror logic from children_map construction
+                    // If link is invalid, it MUST be a root
+                    match by_pid.get(&ppid) {
+                        Some(parent_proc) if is_parent(parent_proc, process) => None,
+                        // Invalid link, or parent not in the list -> Root
+                        _ => Some(process.pid),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    let mut processes: Vec<ProcessInfo> = Vec::new();
+    for root_pid in roots {
+        if let Some(node) = build_process_node(root_pid, &by_pid, &children_map, cpu_count) {
+            processes.push(node);
+        }
+    }
+
+    // Sort root processes by CPU usage (descending)
+    processes.sort_by(|a, b| {
+        b.total_cpu_usage
+            .partial_cmp(&a.total_cpu_usage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    processes
+}
+
+/// Whether `parent` really is `child`'s parent, and not a newer process that was
+/// given the PID of the real (exited) one: a child cannot have started before its parent.
+fn is_parent(parent: &ProcessRecord, child: &ProcessRecord) -> bool {
+    match (parent.start_ticks, child.start_ticks) {
+        // Precise start times also settle reuse within the same second
+        (Some(p_start), Some(c_start)) => c_start >= p_start,
+        _ => {
+            let (p_start, c_start) = (parent.start_time, child.start_time);
+            // Both 0 (likely Access Denied / System Processes) can't tell valid parentage
+            // from PID reuse. Treating the child as a root prevents the "Giant Tree" bug;
+            // system trees come out flatter (services separate from wininit).
+            c_start >= p_start && !(c_start == 0 && p_start == 0)
+        }
+    }
+}
+
+/// Run the monitor loop forever: sample at the configured interval, then feed the
+/// exporter, the history store, the alert rules and finally the sink.
+pub async fn run<S: MonitorSink>(services: MonitorServices, sink: S) {
+    let mut evaluator = AlertEvaluator::default();
+
+    loop {
+        let started = Instant::now();
+        let active = sink.active();
+
+        // Hold the lock only while sampling so commands are not blocked during the sleep
+        let stats = {
+            let mut monitor = services.monitor.lock().unwrap();
+            monitor.set_memory_breakdown(active && sink.memory_breakdown());
+            if active {
+                monitor.sample()
+            } else {
+                monitor.sample_flat()
+            }
+        };
+
+        if active {
+            sink.stats(&stats);
+        }
+
+        // Hand the sample to the metrics endpoint (no-op while it is disabled)
+        services.exporter.publish(&stats);
+
+        // Persist global readings for later range queries, off the async workers
+        let history = services.history.clone();
+        let sample = history::sample_of(&stats);
+        match tokio::task::spawn_blocking(move || history.record(&sample)).await {
+            Ok(Err(e)) => eprintln!("Error recording history: {}", e),
+            Err(e) => eprintln!("Error recording history: {}", e),
+            Ok(Ok(())) => {}
+        }
+
+        // Evaluate user-defined alert rules against this sample
+        evaluate_alerts(
+            &services.alerts,
+            &mut evaluator,
+            &services.monitor,
+            &stats,
+            &sink,
+        );
+
+        // Wait out the rest of the interval; the setting is re-read so changes apply on the next tick
+        // (clamped, in case the config file was edited by hand)
+        let mut interval = Duration::from_millis(
+            services
+                .config
+                .get()
+                .sampling_interval_ms
+                .clamp(MIN_SAMPLING_INTERVAL_MS, MAX_SAMPLING_INTERVAL_MS),
+        );
+        if !active {
+            interval = interval.max(IDLE_INTERVAL);
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval.saturating_sub(started.elapsed())) => {}
+            _ = services.waker.0.notified() => {}
+        }
+    }
+}
+
+fn evaluate_alerts<S: MonitorSink>(
+    alert_store: &AlertStore,
+    evaluator: &mut AlertEvaluator,
+    monitor: &Mutex<Monitor>,
+    stats: &SystemStats,
+    sink: &S,
+) {
+    let rules = alert_store.rules();
+    let listening_ports: Option<HashSet<u16>> = if alerts::needs_ports(&rules) {
+        monitor
+            .lock()
+            .unwrap()
+            .ports()
+            .ok()
+            .map(|all| all.iter().map(|p| p.port).collect())
+    } else {
+        None
+    };
+
+    for event in evaluator.evaluate(&rules, stats, listening_ports.as_ref()) {
+        if let Some(rule) = rules.iter().find(|r| r.id == event.rule_id) {
+            actions::dispatch(&rule.actions, &event, sink.notifier());
+        }
+
+        sink.alert(&event);
+        alert_store.push_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AlertAction, AlertComparison, AlertMetric, AlertRule, AlertSeverity};
+    use crate::source::FixtureSource;
+
+    fn pid_reuse_monitor() -> Monitor {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/pid-reuse.json");
+        Monitor::with_source(Box::new(FixtureSource::load(path).unwrap()))
+    }
+
+    fn find(nodes: &[ProcessInfo], pid: u32) -> Option<&ProcessInfo> {
+        nodes.iter().find_map(|node| {
+            if node.pid == pid {
+                Some(node)
+            } else {
+                find(&node.children, pid)
+            }
+        })
+    }
+
+    fn record(pid: u32, parent: Option<u32>, start_time: u64, ticks: Option<u64>) -> ProcessRecord {
+        ProcessRecord {
+            pid,
+            parent,
+            name: format!("p{}", pid),
+            start_time,
+            start_ticks: ticks,
+            cpu_usage: 0.0,
+            memory: 0,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            thread_count: 1,
+            user: String::new(),
+            exe: String::new(),
+            suspended: false,
+        }
+    }
+
+    fn cpu_rule(threshold: f64) -> AlertRule {
+        AlertRule {
+            id: "cpu".to_string(),
+            name: "CPU".to_string(),
+            enabled: true,
+            metric: AlertMetric::GlobalCpu,
+            comparison: AlertComparison::Above,
+            threshold,
+            duration_secs: 0,
+            hysteresis: 5.0,
+            cooldown_secs: 0,
+            severity: AlertSeverity::Warning,
+            actions: vec![AlertAction::Notify],
+        }
+    }
+
+    #[test]
+    fn fixtures_are_replays() {
+        assert!(pid_reuse_monitor().is_replay());
+    }
+
+    #[test]
+    fn reused_pid_is_not_attached_to_the_old_parent() {
+        let mut monitor = pid_reuse_monitor();
+        for _ in 0..3 {
+            let stats = monitor.sample();
+            let roots: Vec<u32> = stats.top_processes.iter().map(|p| p.pid).collect();
+
+            // `orphan` started before the `worker` now holding its parent's PID
+            let worker = find(&stats.top_processes, 250).unwrap();
+            assert!(worker.children.iter().all(|c| c.pid != 300));
+            assert!(roots.contains(&300));
+
+            // Genuine links are kept
+            let sshd = find(&stats.top_processes, 200).unwrap();
+            assert!(sshd.children.iter().any(|c| c.pid == 210));
+            assert!(!roots.contains(&200));
+
+            // Unknown start times on both sides can't prove parentage
+            assert!(roots.contains(&401));
+        }
+    }
+
+    #[test]
+    fn subtree_leaves_out_reused_pid_children() {
+        let mut monitor = pid_reuse_monitor();
+        monitor.sample();
+        let pids: Vec<u32> = monitor.subtree(250).iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![250]);
+    }
+
+    #[test]
+    fn start_ticks_settle_reuse_within_a_second() {
+        let parent = record(10, None, 500, Some(5_000));
+        assert!(is_parent(&parent, &record(11, Some(10), 500, Some(5_001))));
+        assert!(!is_parent(&parent, &record(11, Some(10), 500, Some(4_999))));
+        // Without ticks the same second is taken as valid
+        let parent = record(10, None, 500, None);
+        assert!(is_parent(&parent, &record(11, Some(10), 500, None)));
+    }
+
+    #[test]
+    fn alert_fires_and_clears_at_threshold() {
+        let mut monitor = pid_reuse_monitor();
+        let mut evaluator = AlertEvaluator::default();
+        let rules = vec![cpu_rule(90.0)];
+        let mut fired = Vec::new();
+        // Frames: 12.5%, 96%, 97%, then wrapping around to 12.5% and 96% again
+        for _ in 0..5 {
+            let stats = monitor.sample();
+            fired.push(evaluator.evaluate(&rules, &stats, None).len());
+        }
+        // Fires on crossing, stays quiet while active, clears below 85% and fires again
+        assert_eq!(fired, vec![0, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn alert_stays_quiet_below_threshold() {
+        let mut monitor = pid_reuse_monitor();
+        let mut evaluator = AlertEvaluator::default();
+        let rules = vec![cpu_rule(97.0)];
+        for _ in 0..3 {
+            let stats = monitor.sample();
+            assert!(evaluator.evaluate(&rules, &stats, None).is_empty());
+        }
+    }
+}
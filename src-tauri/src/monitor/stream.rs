@@ -0,0 +1,413 @@
+use super::set_totals;
+use super::view::{ProcessView, ViewRow};
+use crate::error::CommandResult;
+use crate::models::{
+    ProcessChange, ProcessInfo, ProcessRow, StatsFrame, StatsFrameKind, SystemStats,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Stream state shared between the monitor loop and the subscription commands
+pub type SharedStatsStream = Arc<Mutex<StatsStream>>;
+
+/// Turns successive samples into numbered frames that only carry what changed
+/// in the subscribed view. Keeps the last sample so a client can be
+/// (re)synchronized, or change its view, at any time.
+#[derive(Default)]
+pub struct StatsStream {
+    seq: u64,
+    view: ProcessView,
+    stats: SystemStats,
+    tree: Vec<ProcessInfo>,
+    rows: HashMap<u32, ProcessRow>,
+    order: Vec<u32>,
+    total_rows: usize,
+}
+
+impl StatsStream {
+    /// Record a new sample and return its delta against the previous one
+    pub fn advance(&mut self, stats: &SystemStats) -> StatsFrame {
+        self.stats = globals(stats);
+        self.tree = stats.top_processes.clone();
+
+        let (rows, order, total_rows) = self.page();
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for pid in &order {
+            let row = &rows[pid];
+            match self.rows.get(pid) {
+                None => added.push(row.clone()),
+                Some(old) if old != row => changed.push(diff(old, row)),
+                Some(_) => {}
+            }
+        }
+        let removed = self
+            .order
+            .iter()
+            .filter(|pid| !rows.contains_key(pid))
+            .copied()
+            .collect();
+        let order_changed = order != self.order;
+
+        self.seq += 1;
+        self.rows = rows;
+        self.order = order;
+        self.total_rows = total_rows;
+
+        StatsFrame {
+            seq: self.seq,
+            kind: StatsFrameKind::Delta,
+            stats: self.stats.clone(),
+            total_rows,
+            added,
+            removed,
+            changed,
+            order: order_changed.then(|| self.order.clone()),
+        }
+    }
+
+    /// The whole view as of the last frame, numbered like it
+    pub fn snapshot(&self) -> StatsFrame {
+        StatsFrame {
+            seq: self.seq,
+            kind: StatsFrameKind::Snapshot,
+            stats: self.stats.clone(),
+            total_rows: self.total_rows,
+            added: self
+                .order
+                .iter()
+                .map(|pid| self.rows[pid].clone())
+                .collect(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            order: Some(self.order.clone()),
+        }
+    }
+
+    pub fn view(&self) -> &ProcessView {
+        &self.view
+    }
+
+    /// Switch to another view and return its snapshot. The snapshot gets a new
+    /// sequence number so frames computed for the old view are recognisably stale.
+    /// Fails, keeping the current view, if the filter doesn't parse.
+    pub fn subscribe(&mut self, view: ProcessView) -> CommandResult<StatsFrame> {
+        view.query()?;
+        self.view = view;
+        let (rows, order, total_rows) = self.page();
+        self.seq += 1;
+        self.rows = rows;
+        self.order = order;
+        self.total_rows = total_rows;
+        Ok(self.snapshot())
+    }
+
+    fn page(&mut self) -> (HashMap<u32, ProcessRow>, Vec<u32>, usize) {
+        set_totals(&mut self.tree, self.view.aggregate);
+        let (page, total_rows) = self.view.page(&self.tree);
+        let order = page.iter().map(|r| r.process.pid).collect();
+        let rows = page.iter().map(|r| (r.process.pid, row(r))).collect();
+        (rows, order, total_rows)
+    }
+}
+
+/// Copy of the global readings, without the process tree
+fn globals(stats: &SystemStats) -> SystemStats {
+    SystemStats {
+        timestamp: stats.timestamp,
+        interval_ms: stats.interval_ms,
+        cpu_usage: stats.cpu_usage,
+        cpu_cores: stats.cpu_cores.clone(),
+        process_count: stats.process_count,
+        thread_count: stats.thread_count,
+        memory_used: stats.memory_used,
+        memory_total: stats.memory_total,
+        network_up: stats.network_up,
+        network_down: stats.network_down,
+        disk_read: stats.disk_read,
+        disk_write: stats.disk_write,
+        gpu_usage: stats.gpu_usage,
+        components: stats.components.clone(),
+        top_processes: Vec::new(),
+    }
+}
+
+fn row(view_row: &ViewRow) -> ProcessRow {
+    let node = view_row.process;
+    ProcessRow {
+        pid: node.pid,
+        parent: view_row.parent,
+        depth: view_row.depth,
+        child_count: node.children.len(),
+        name: node.name.clone(),
+        start_time: node.start_time,
+        start_ticks: node.start_ticks,
+        cpu_usage: node.cpu_usage,
+        total_cpu_usage: node.total_cpu_usage,
+        memory_usage: node.memory_usage,
+        total_memory_usage: node.total_memory_usage,
+        memory_breakdown: node.memory_breakdown,
+        disk_read: node.disk_read,
+        disk_write: node.disk_write,
+        total_disk_read: node.total_disk_read,
+        total_disk_write: node.total_disk_write,
+        thread_count: node.thread_count,
+        suspended: node.suspended,
+    }
+}
+
+fn diff(old: &ProcessRow, new: &ProcessRow) -> ProcessChange {
+    fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+        (old != new).then(|| new.clone())
+    }
+
+    ProcessChange {
+        pid: new.pid,
+        parent: changed(&old.parent, &new.parent),
+        depth: changed(&old.depth, &new.depth),
+        child_count: changed(&old.child_count, &new.child_count),
+        name: changed(&old.name, &new.name),
+        start_time: changed(&old.start_time, &new.start_time),
+        start_ticks: changed(&old.start_ticks, &new.start_ticks),
+        cpu_usage: changed(&old.cpu_usage, &new.cpu_usage),
+        total_cpu_usage: changed(&old.total_cpu_usage, &new.total_cpu_usage),
+        memory_usage: changed(&old.memory_usage, &new.memory_usage),
+        total_memory_usage: changed(&old.total_memory_usage, &new.total_memory_usage),
+        memory_breakdown: changed(&old.memory_breakdown, &new.memory_breakdown),
+        disk_read: changed(&old.disk_read, &new.disk_read),
+        disk_write: changed(&old.disk_write, &new.disk_write),
+        total_disk_read: changed(&old.total_disk_read, &new.total_disk_read),
+        total_disk_write: changed(&old.total_disk_write, &new.total_disk_write),
+        thread_count: changed(&old.thread_count, &new.thread_count),
+        suspended: changed(&old.suspended, &new.suspended),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::view::ViewMode;
+
+    fn process(pid: u32, name: &str, cpu_usage: f32, children: Vec<ProcessInfo>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.into(),
+            start_time: 0,
+            start_ticks: Some(pid as u64 * 100),
+            user: String::new(),
+            exe: String::new(),
+            cpu_usage,
+            total_cpu_usage: cpu_usage,
+            memory_usage: 1024,
+            total_memory_usage: 1024,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            total_disk_read: 0,
+            total_disk_write: 0,
+            thread_count: 1,
+            suspended: false,
+            children,
+        }
+    }
+
+    fn stats(processes: Vec<ProcessInfo>) -> SystemStats {
+        SystemStats {
+            process_count: processes.len(),
+            top_processes: processes,
+            ..Default::default()
+        }
+    }
+
+    fn pids(rows: &[ProcessRow]) -> Vec<u32> {
+        rows.iter().map(|r| r.pid).collect()
+    }
+
+    /// What a client holds: the rows of the view in display order
+    #[derive(Default)]
+    struct Client {
+        seq: u64,
+        rows: HashMap<u32, ProcessRow>,
+        order: Vec<u32>,
+    }
+
+    impl Client {
+        fn apply(&mut self, frame: &StatsFrame) {
+            match frame.kind {
+                StatsFrameKind::Snapshot => self.rows.clear(),
+                StatsFrameKind::Delta => assert_eq!(frame.seq, self.seq + 1),
+            }
+            self.seq = frame.seq;
+            for pid in &frame.removed {
+                self.rows.remove(pid);
+            }
+            for row in &frame.added {
+                self.rows.insert(row.pid, row.clone());
+            }
+            for change in &frame.changed {
+                let row = self.rows.get_mut(&change.pid).unwrap();
+                patch(row, change);
+            }
+            if let Some(order) = &frame.order {
+                self.order = order.clone();
+            }
+        }
+
+        fn rows(&self) -> Vec<ProcessRow> {
+            self.order
+                .iter()
+                .map(|pid| self.rows[pid].clone())
+                .collect()
+        }
+    }
+
+    fn patch(row: &mut ProcessRow, change: &ProcessChange) {
+        fn set<T: Clone>(field: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *field = value.clone();
+            }
+        }
+        set(&mut row.parent, &change.parent);
+        set(&mut row.depth, &change.depth);
+        set(&mut row.child_count, &change.child_count);
+        set(&mut row.name, &change.name);
+        set(&mut row.start_time, &change.start_time);
+        set(&mut row.start_ticks, &change.start_ticks);
+        set(&mut row.cpu_usage, &change.cpu_usage);
+        set(&mut row.total_cpu_usage, &change.total_cpu_usage);
+        set(&mut row.memory_usage, &change.memory_usage);
+        set(&mut row.total_memory_usage, &change.total_memory_usage);
+        set(&mut row.memory_breakdown, &change.memory_breakdown);
+        set(&mut row.disk_read, &change.disk_read);
+        set(&mut row.disk_write, &change.disk_write);
+        set(&mut row.total_disk_read, &change.total_disk_read);
+        set(&mut row.total_disk_write, &change.total_disk_write);
+        set(&mut row.thread_count, &change.thread_count);
+        set(&mut row.suspended, &change.suspended);
+    }
+
+    #[test]
+    fn deltas_carry_added_removed_changed_and_order() {
+        let mut stream = StatsStream::default();
+
+        let first = stream.advance(&stats(vec![
+            process(1, "a", 10.0, vec![]),
+            process(2, "b", 20.0, vec![]),
+            process(3, "c", 30.0, vec![]),
+        ]));
+        assert_eq!(first.seq, 1);
+        assert_eq!(pids(&first.added), [3, 2, 1]);
+        assert_eq!(first.order, Some(vec![3, 2, 1]));
+
+        let second = stream.advance(&stats(vec![
+            process(1, "a", 50.0, vec![]),
+            process(2, "b", 20.0, vec![]),
+            process(4, "d", 5.0, vec![]),
+        ]));
+        assert_eq!(second.seq, 2);
+        assert!(second.kind == StatsFrameKind::Delta);
+        assert_eq!(pids(&second.added), [4]);
+        assert_eq!(second.removed, [3]);
+        assert_eq!(second.changed.len(), 1);
+        let change = &second.changed[0];
+        assert_eq!(change.pid, 1);
+        assert_eq!(change.cpu_usage, Some(50.0));
+        assert_eq!(change.total_cpu_usage, Some(50.0));
+        assert_eq!(change.name, None);
+        assert_eq!(change.memory_usage, None);
+        assert_eq!(second.order, Some(vec![1, 2, 4]));
+
+        // Nothing moved: an empty delta without an order
+        let third = stream.advance(&stats(vec![
+            process(1, "a", 50.0, vec![]),
+            process(2, "b", 20.0, vec![]),
+            process(4, "d", 5.0, vec![]),
+        ]));
+        assert_eq!(third.seq, 3);
+        assert!(third.added.is_empty() && third.removed.is_empty() && third.changed.is_empty());
+        assert_eq!(third.order, None);
+    }
+
+    #[test]
+    fn subscribe_bumps_seq_and_keeps_the_view_on_bad_filters() {
+        let mut stream = StatsStream::default();
+        stream.advance(&stats(vec![
+            process(1, "bash", 10.0, vec![]),
+            process(2, "zsh", 20.0, vec![]),
+        ]));
+
+        let snapshot = stream
+            .subscribe(ProcessView {
+                filter: "name:b*".into(),
+                ..ProcessView::default()
+            })
+            .unwrap();
+        assert_eq!(snapshot.seq, 2);
+        assert!(snapshot.kind == StatsFrameKind::Snapshot);
+        assert_eq!(pids(&snapshot.added), [1]);
+        assert_eq!(snapshot.order, Some(vec![1]));
+
+        let error = stream.subscribe(ProcessView {
+            filter: "cpu>".into(),
+            ..ProcessView::default()
+        });
+        assert!(error.is_err());
+        assert_eq!(stream.view().filter, "name:b*");
+        assert_eq!(stream.snapshot().seq, 2);
+    }
+
+    #[test]
+    fn snapshot_plus_deltas_rebuild_the_same_rows() {
+        let samples = [
+            vec![process(
+                1,
+                "init",
+                1.0,
+                vec![process(
+                    2,
+                    "shell",
+                    5.0,
+                    vec![process(3, "vim", 2.0, vec![])],
+                )],
+            )],
+            vec![process(
+                1,
+                "init",
+                1.0,
+                vec![
+                    process(2, "shell", 5.0, vec![]),
+                    process(3, "vim", 9.0, vec![process(5, "rg", 40.0, vec![])]),
+                ],
+            )],
+            vec![process(
+                1,
+                "init",
+                2.0,
+                vec![
+                    process(5, "rg", 45.0, vec![]),
+                    process(6, "cc", 60.0, vec![]),
+                ],
+            )],
+        ];
+
+        let mut stream = StatsStream::default();
+        let mut client = Client::default();
+        client.apply(
+            &stream
+                .subscribe(ProcessView {
+                    mode: ViewMode::Tree,
+                    aggregate: true,
+                    ..ProcessView::default()
+                })
+                .unwrap(),
+        );
+        for sample in samples {
+            client.apply(&stream.advance(&stats(sample)));
+            let snapshot = stream.snapshot();
+            assert_eq!(client.seq, snapshot.seq);
+            assert!(client.rows() == snapshot.added);
+        }
+        assert_eq!(pids(&client.rows()), [1, 6, 5]);
+        assert_eq!(client.rows()[1].parent, Some(1));
+    }
+}
@@ -0,0 +1,332 @@
+use crate::error::CommandResult;
+use crate::models::ProcessInfo;
+use crate::query::Query;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Column a process list is ordered by
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    Cpu,
+    Memory,
+    Pss,
+    Uss,
+    Swap,
+    DiskRead,
+    DiskWrite,
+    Threads,
+    Name,
+    Pid,
+    StartTime,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Cpu => SortKey::Memory,
+            SortKey::Memory => SortKey::Pss,
+            SortKey::Pss => SortKey::Uss,
+            SortKey::Uss => SortKey::Swap,
+            SortKey::Swap => SortKey::DiskRead,
+            SortKey::DiskRead => SortKey::DiskWrite,
+            SortKey::DiskWrite => SortKey::Threads,
+            SortKey::Threads => SortKey::Name,
+            SortKey::Name => SortKey::Pid,
+            SortKey::Pid => SortKey::StartTime,
+            SortKey::StartTime => SortKey::Cpu,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Cpu => "CPU",
+            SortKey::Memory => "Memory",
+            SortKey::Pss => "PSS",
+            SortKey::Uss => "USS",
+            SortKey::Swap => "Swap",
+            SortKey::DiskRead => "Read",
+            SortKey::DiskWrite => "Write",
+            SortKey::Threads => "Threads",
+            SortKey::Name => "Name",
+            SortKey::Pid => "PID",
+            SortKey::StartTime => "Started",
+        }
+    }
+
+    /// Whether the key reads naturally from largest to smallest
+    pub fn descending_by_default(self) -> bool {
+        !matches!(self, SortKey::Name | SortKey::Pid | SortKey::StartTime)
+    }
+
+    /// Whether sorting by this key needs `MemoryBreakdown`s
+    pub fn needs_memory_breakdown(self) -> bool {
+        matches!(self, SortKey::Pss | SortKey::Uss | SortKey::Swap)
+    }
+
+    /// The breakdown figure this key sorts by, if it is one
+    pub fn breakdown_value(self, process: &ProcessInfo) -> Option<u64> {
+        let breakdown = process.memory_breakdown?;
+        match self {
+            SortKey::Pss => breakdown.pss,
+            SortKey::Uss => breakdown.uss,
+            SortKey::Swap => breakdown.swap,
+            _ => None,
+        }
+    }
+
+    /// Ascending order; metrics compare the `total_*` values shown in the table.
+    /// Breakdown figures are per process, and unknown ones sort lowest.
+    pub fn compare(self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        match self {
+            SortKey::Cpu => a
+                .total_cpu_usage
+                .partial_cmp(&b.total_cpu_usage)
+                .unwrap_or(Ordering::Equal),
+            SortKey::Memory => a.total_memory_usage.cmp(&b.total_memory_usage),
+            SortKey::Pss | SortKey::Uss | SortKey::Swap => {
+                self.breakdown_value(a).cmp(&self.breakdown_value(b))
+            }
+            SortKey::DiskRead => a.total_disk_read.cmp(&b.total_disk_read),
+            SortKey::DiskWrite => a.total_disk_write.cmp(&b.total_disk_write),
+            SortKey::Threads => a.thread_count.cmp(&b.thread_count),
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::StartTime => a.start_time.cmp(&b.start_time),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    #[default]
+    Flat,
+    Tree,
+}
+
+/// What a client wants to see of the process table: order, filter, shape and
+/// the window of rows it is displaying
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ProcessView {
+    pub sort: SortKey,
+    pub descending: bool,
+    pub filter: String, // Query expression (see `crate::query`); empty keeps everything
+    pub mode: ViewMode,
+    pub collapsed: HashSet<u32>, // Tree mode: nodes whose children are hidden (ignored while filtering)
+    pub collapse_all: bool, // Fold every branch instead; `collapsed` then lists the unfolded ones
+    pub aggregate: bool,    // Sum descendants into the `total_*` values (see `set_totals`)
+    pub memory_breakdown: bool, // Read USS/PSS/shared/swap per process; costly, so opt-in
+    pub offset: usize,
+    pub limit: Option<usize>, // Rows from `offset` on; None for all of them
+}
+
+impl Default for ProcessView {
+    fn default() -> Self {
+        Self {
+            sort: SortKey::Cpu,
+            descending: true,
+            filter: String::new(),
+            mode: ViewMode::Flat,
+            collapsed: HashSet::new(),
+            collapse_all: false,
+            aggregate: false,
+            memory_breakdown: false,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+/// One line of a view
+pub struct ViewRow<'a> {
+    pub depth: usize,
+    pub parent: Option<u32>,
+    pub process: &'a ProcessInfo,
+}
+
+/// Flattens the process tree into rows, honouring sort, filter and folded branches
+struct TreeWalk<'a> {
+    view: &'a ProcessView,
+    matches: &'a dyn Fn(&ProcessInfo) -> bool,
+    filtering: bool,
+}
+
+impl<'t> TreeWalk<'_> {
+    /// Returns whether any node of `nodes` (or below) was kept
+    fn visit(
+        &self,
+        nodes: &'t [ProcessInfo],
+        parent: Option<u32>,
+        depth: usize,
+        rows: &mut Vec<ViewRow<'t>>,
+    ) -> bool {
+        let mut sorted: Vec<&ProcessInfo> = nodes.iter().collect();
+        sorted.sort_by(|a, b| self.view.compare(a, b));
+
+        let mut any = false;
+        for node in sorted {
+            let index = rows.len();
+            rows.push(ViewRow {
+                depth,
+                parent,
+                process: node,
+            });
+
+            // While filtering every branch is walked so matches deep in the tree stay visible
+            let expanded = self.filtering || !self.view.is_collapsed(node.pid);
+            let child_match =
+                expanded && self.visit(&node.children, Some(node.pid), depth + 1, rows);
+
+            if (self.matches)(node) || child_match {
+                any = true;
+            } else {
+                rows.truncate(index);
+            }
+        }
+        any
+    }
+}
+
+impl ProcessView {
+    /// Ordering of two processes under this view's key and direction. Equal keys
+    /// fall back to ascending PIDs, so the order doesn't change between samples.
+    pub fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        let ordering = self.sort.compare(a, b);
+        let ordering = if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then_with(|| a.pid.cmp(&b.pid))
+    }
+
+    /// Whether a tree node's children are hidden when not filtering
+    pub fn is_collapsed(&self, pid: u32) -> bool {
+        self.collapse_all != self.collapsed.contains(&pid)
+    }
+
+    /// Whether samples for this view need `MemoryBreakdown`s, to show or to sort by
+    pub fn wants_memory_breakdown(&self) -> bool {
+        self.memory_breakdown || self.sort.needs_memory_breakdown()
+    }
+
+    /// The parsed filter
+    pub fn query(&self) -> CommandResult<Query> {
+        Query::parse(&self.filter)
+    }
+
+    /// Every row the view shows, in display order. A filter that doesn't parse keeps everything.
+    pub fn rows<'a>(&self, tree: &'a [ProcessInfo]) -> Vec<ViewRow<'a>> {
+        let query = self.query().unwrap_or_default();
+        let matches = |p: &ProcessInfo| query.matches(p);
+
+        let mut rows = Vec::new();
+        match self.mode {
+            ViewMode::Tree => {
+                let walk = TreeWalk {
+                    view: self,
+                    matches: &matches,
+                    filtering: !query.is_empty(),
+                };
+                walk.visit(tree, None, 0, &mut rows);
+            }
+            ViewMode::Flat => {
+                fn flatten<'a>(
+                    nodes: &'a [ProcessInfo],
+                    parent: Option<u32>,
+                    out: &mut Vec<ViewRow<'a>>,
+                ) {
+                    for node in nodes {
+                        out.push(ViewRow {
+                            depth: 0,
+                            parent,
+                            process: node,
+                        });
+                        flatten(&node.children, Some(node.pid), out);
+                    }
+                }
+                flatten(tree, None, &mut rows);
+                rows.retain(|r| matches(r.process));
+                rows.sort_by(|a, b| self.compare(a.process, b.process));
+            }
+        }
+        rows
+    }
+
+    /// The rows inside the window, and how many rows the whole view has
+    pub fn page<'a>(&self, tree: &'a [ProcessInfo]) -> (Vec<ViewRow<'a>>, usize) {
+        let mut rows = self.rows(tree);
+        let total = rows.len();
+        let end = match self.limit {
+            Some(limit) => self.offset.saturating_add(limit).min(total),
+            None => total,
+        };
+        rows.truncate(end);
+        rows.drain(..self.offset.min(end));
+        (rows, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: "worker".into(),
+            start_time: 0,
+            start_ticks: None,
+            user: String::new(),
+            exe: String::new(),
+            cpu_usage,
+            total_cpu_usage: cpu_usage,
+            memory_usage: 0,
+            total_memory_usage: 0,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            total_disk_read: 0,
+            total_disk_write: 0,
+            thread_count: 1,
+            suspended: false,
+            children: Vec::new(),
+        }
+    }
+
+    fn page(view: &ProcessView, tree: &[ProcessInfo]) -> Vec<u32> {
+        view.page(tree).0.iter().map(|r| r.process.pid).collect()
+    }
+
+    #[test]
+    fn equal_keys_page_by_pid() {
+        let view = ProcessView {
+            offset: 1,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let tree = vec![
+            process(30, 0.0),
+            process(10, 5.0),
+            process(20, 0.0),
+            process(40, 0.0),
+        ];
+        assert_eq!(page(&view, &tree), [20, 30]);
+
+        // The same processes listed in another order, as a new sample may
+        let mut shuffled = tree.clone();
+        shuffled.reverse();
+        assert_eq!(page(&view, &shuffled), [20, 30]);
+
+        let ascending = ProcessView {
+            descending: false,
+            ..view
+        };
+        assert_eq!(page(&ascending, &tree), [30, 40]);
+        assert_eq!(page(&ascending, &shuffled), [30, 40]);
+    }
+}
@@ -0,0 +1,512 @@
+//! Process filter expressions shared by the process view, the CLI and alert rules.
+//!
+//! A query is a list of terms that must all match, e.g.
+//! `cpu>20 mem>500MB user:root name~"chrom" exe:/usr/bin/*`:
+//!
+//! - `field>value`, `>=`, `<`, `<=`, `=`, `!=` compare numbers: `pid`, `cpu` (percent),
+//!   `mem`/`memory`, `read`/`disk_read`, `write`/`disk_write` (bytes, with an optional
+//!   `B`, `KB`, `MB`, `GB` or `TB` suffix) and `threads`
+//! - `field:value` matches text exactly, with `*` and `?` wildcards: `name`, `user`, `exe`
+//! - `field~value` matches text containing `value`
+//! - a bare word matches names containing it, or the PID it spells
+//! - `!` in front of a term negates it
+//!
+//! Text comparisons ignore case. Values with spaces go in double quotes.
+
+use crate::error::{CommandError, CommandResult};
+use crate::models::{ProcessDetails, ProcessInfo};
+
+const OPERATION: &str = "parse_query";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Pid,
+    Cpu,
+    Memory,
+    DiskRead,
+    DiskWrite,
+    Threads,
+    Name,
+    User,
+    Exe,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "pid" => Field::Pid,
+            "cpu" => Field::Cpu,
+            "mem" | "memory" => Field::Memory,
+            "read" | "disk_read" => Field::DiskRead,
+            "write" | "disk_write" => Field::DiskWrite,
+            "threads" => Field::Threads,
+            "name" => Field::Name,
+            "user" => Field::User,
+            "exe" => Field::Exe,
+            _ => return None,
+        })
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Field::Name | Field::User | Field::Exe)
+    }
+
+    fn is_bytes(self) -> bool {
+        matches!(self, Field::Memory | Field::DiskRead | Field::DiskWrite)
+    }
+}
+
+/// Something a query can be evaluated against. Fields it doesn't have never match.
+pub trait Queryable {
+    fn number(&self, field: Field) -> Option<f64>;
+    fn text(&self, field: Field) -> Option<&str>;
+}
+
+impl Queryable for ProcessInfo {
+    fn number(&self, field: Field) -> Option<f64> {
+        Some(match field {
+            Field::Pid => self.pid as f64,
+            Field::Cpu => self.cpu_usage as f64,
+            Field::Memory => self.memory_usage as f64,
+            Field::DiskRead => self.disk_read as f64,
+            Field::DiskWrite => self.disk_write as f64,
+            Field::Threads => self.thread_count as f64,
+            _ => return None,
+        })
+    }
+
+    fn text(&self, field: Field) -> Option<&str> {
+        match field {
+            Field::Name => Some(&self.name),
+            Field::User => Some(&self.user),
+            Field::Exe => Some(&self.exe),
+            _ => None,
+        }
+    }
+}
+
+impl Queryable for ProcessDetails {
+    fn number(&self, field: Field) -> Option<f64> {
+        Some(match field {
+            Field::Pid => self.pid as f64,
+            Field::Cpu => self.cpu_usage as f64,
+            Field::Memory => self.memory_usage as f64,
+            _ => return None,
+        })
+    }
+
+    fn text(&self, field: Field) -> Option<&str> {
+        match field {
+            Field::Name => Some(&self.name),
+            Field::User => Some(&self.user),
+            Field::Exe => Some(&self.exe),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Equal,
+    NotEqual,
+    Glob, // `:`
+    Contains,
+}
+
+#[derive(Clone, Debug)]
+enum Condition {
+    Number(Field, Op, f64),
+    Text(Field, Op, String), // Lowercased pattern
+    Word(String),            // Lowercased bare word
+}
+
+#[derive(Clone, Debug)]
+struct Term {
+    negated: bool,
+    condition: Condition,
+}
+
+/// A parsed filter expression; the empty query matches everything
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> CommandResult<Self> {
+        let mut parser = Parser {
+            chars: input.chars().collect(),
+            position: 0,
+        };
+        let mut terms = Vec::new();
+        while let Some(term) = parser.term()? {
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, target: &impl Queryable) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.condition.matches(target) != term.negated)
+    }
+}
+
+impl Condition {
+    fn matches(&self, target: &impl Queryable) -> bool {
+        match self {
+            Condition::Number(field, op, value) => {
+                let Some(actual) = target.number(*field) else {
+                    return false;
+                };
+                match op {
+                    Op::Greater => actual > *value,
+                    Op::GreaterEq => actual >= *value,
+                    Op::Less => actual < *value,
+                    Op::LessEq => actual <= *value,
+                    Op::NotEqual => actual != *value,
+                    _ => actual == *value,
+                }
+            }
+            Condition::Text(field, op, pattern) => {
+                let Some(actual) = target.text(*field) else {
+                    return false;
+                };
+                let actual = actual.to_lowercase();
+                match op {
+                    Op::Contains => actual.contains(pattern.as_str()),
+                    Op::NotEqual => !glob(pattern, &actual),
+                    _ => glob(pattern, &actual),
+                }
+            }
+            Condition::Word(word) => {
+                target
+                    .text(Field::Name)
+                    .is_some_and(|name| name.to_lowercase().contains(word.as_str()))
+                    || target
+                        .number(Field::Pid)
+                        .is_some_and(|pid| pid.to_string() == *word)
+            }
+        }
+    }
+}
+
+/// Whole-string match with `*` (any run) and `?` (any one character)
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, t));
+            p += 1;
+        } else if let Some((after, tried)) = star {
+            // Let the last `*` swallow one more character
+            p = after;
+            t = tried + 1;
+            star = Some((after, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self, message: impl Into<String>) -> CommandError {
+        CommandError::invalid_argument(
+            OPERATION,
+            format!("{} at column {}", message.into(), self.position + 1),
+        )
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn term(&mut self) -> CommandResult<Option<Term>> {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        if self.peek().is_none() {
+            return Ok(None);
+        }
+
+        let negated = self.peek() == Some('!');
+        if negated {
+            self.position += 1;
+        }
+
+        // `field<op>value`, or else a bare word
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.position += 1;
+        }
+        let name: String = self.chars[start..self.position].iter().collect();
+        let op = if name.is_empty() { None } else { self.op() };
+
+        let condition = match op {
+            Some(op) => {
+                let field = Field::parse(&name).ok_or_else(|| {
+                    self.error(format!(
+                        "Unknown field '{}' (expected pid, cpu, mem, read, write, threads, name, user or exe)",
+                        name
+                    ))
+                })?;
+                let value = self.value()?;
+                if value.is_empty() {
+                    return Err(self.error(format!("Missing value for '{}'", name)));
+                }
+                self.condition(&name, field, op, value)?
+            }
+            None => {
+                // An operator without a field is a mistake (`cpu > 20`), not a name
+                if name.is_empty() && self.op().is_some() {
+                    self.position = start;
+                    return Err(self.error("Expected a field name before the operator"));
+                }
+                self.position = start;
+                let word = self.value()?;
+                if word.is_empty() {
+                    return Err(self.error("Expected a term after '!'"));
+                }
+                Condition::Word(word.to_lowercase())
+            }
+        };
+
+        Ok(Some(Term { negated, condition }))
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        let next = self.chars.get(self.position + 1).copied();
+        let (op, len) = match (self.peek()?, next) {
+            ('>', Some('=')) => (Op::GreaterEq, 2),
+            ('<', Some('=')) => (Op::LessEq, 2),
+            ('!', Some('=')) => (Op::NotEqual, 2),
+            ('=', Some('=')) => (Op::Equal, 2),
+            ('>', _) => (Op::Greater, 1),
+            ('<', _) => (Op::Less, 1),
+            ('=', _) => (Op::Equal, 1),
+            (':', _) => (Op::Glob, 1),
+            ('~', _) => (Op::Contains, 1),
+            _ => return None,
+        };
+        self.position += len;
+        Some(op)
+    }
+
+    /// A double-quoted string or a run of non-whitespace characters
+    fn value(&mut self) -> CommandResult<String> {
+        if self.peek() == Some('"') {
+            let start = self.position;
+            self.position += 1;
+            let mut value = String::new();
+            loop {
+                match self.peek() {
+                    None => {
+                        self.position = start;
+                        return Err(self.error("Unterminated quote"));
+                    }
+                    Some('"') => break,
+                    Some('\\') if self.chars.get(self.position + 1) == Some(&'"') => {
+                        value.push('"');
+                        self.position += 2;
+                        continue;
+                    }
+                    Some(c) => value.push(c),
+                }
+                self.position += 1;
+            }
+            self.position += 1;
+            return Ok(value);
+        }
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.position += 1;
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    fn condition(
+        &self,
+        name: &str,
+        field: Field,
+        op: Op,
+        value: String,
+    ) -> CommandResult<Condition> {
+        if field.is_text() {
+            return match op {
+                Op::Glob | Op::Equal | Op::NotEqual | Op::Contains => {
+                    Ok(Condition::Text(field, op, value.to_lowercase()))
+                }
+                _ => Err(self.error(format!("'{}' is text; use ':', '~' or '!='", name))),
+            };
+        }
+
+        let op = match op {
+            Op::Contains => {
+                return Err(self.error(format!("'~' only applies to text, not '{}'", name)))
+            }
+            Op::Glob => Op::Equal,
+            op => op,
+        };
+        let number = if field.is_bytes() {
+            parse_bytes(&value)
+        } else if field == Field::Cpu {
+            value.strip_suffix('%').unwrap_or(&value).parse().ok()
+        } else {
+            value.parse().ok()
+        };
+        let number = number.ok_or_else(|| self.error(format!("Invalid number '{}'", value)))?;
+        Ok(Condition::Number(field, op, number))
+    }
+}
+
+/// `500MB`, `1.5G`, `4096` -> bytes (binary units, as displayed)
+fn parse_bytes(value: &str) -> Option<f64> {
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let scale = match unit.to_uppercase().trim_end_matches("/S") {
+        "" | "B" => 1u64,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return None,
+    };
+    Some(number * scale as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn process(pid: u32, name: &str, memory_usage: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.into(),
+            start_time: 0,
+            start_ticks: None,
+            user: "root".into(),
+            exe: format!("/usr/bin/{}", name),
+            cpu_usage: 25.0,
+            total_cpu_usage: 25.0,
+            memory_usage,
+            total_memory_usage: memory_usage,
+            memory_breakdown: None,
+            disk_read: 0,
+            disk_write: 0,
+            total_disk_read: 0,
+            total_disk_write: 0,
+            thread_count: 4,
+            suspended: false,
+            children: Vec::new(),
+        }
+    }
+
+    fn matches(query: &str, target: &ProcessInfo) -> bool {
+        Query::parse(query).unwrap().matches(target)
+    }
+
+    const GIB: u64 = 1 << 30;
+
+    #[test]
+    fn negation_inverts_any_term() {
+        let firefox = process(10, "firefox", GIB);
+        assert!(matches("!chrome", &firefox));
+        assert!(!matches("!fire", &firefox));
+        assert!(matches("!cpu>50", &firefox));
+        assert!(!matches("!name:firefox", &firefox));
+        assert!(matches("user:root !pid=11", &firefox));
+    }
+
+    #[test]
+    fn memory_takes_binary_units() {
+        let small = process(1, "small", GIB);
+        let large = process(2, "large", 2 * GIB);
+        assert!(!matches("mem>1.5G", &small));
+        assert!(matches("mem>1.5G", &large));
+        assert!(matches("mem>=2GB", &large));
+        assert!(matches("mem<1536MiB", &small));
+        assert!(matches(&format!("mem={}", GIB), &small));
+        assert!(matches("mem:1gb", &small));
+    }
+
+    #[test]
+    fn glob_matches_whole_text_and_tilde_any_part() {
+        let p = process(1, "chromium-browser", GIB);
+        assert!(matches("name:chrom*", &p));
+        assert!(matches("name:CHROMIUM-?ROWSER", &p));
+        assert!(!matches("name:chrom", &p));
+        assert!(!matches("name:*chrom", &p));
+        assert!(matches("name~chrom", &p));
+        assert!(matches("name~browser", &p));
+        assert!(matches("exe:/usr/bin/*", &p));
+        assert!(matches("exe~\"bin/chrom\"", &p));
+    }
+
+    #[test]
+    fn not_equal_on_text_is_a_negated_glob() {
+        let p = process(1, "bash", GIB);
+        assert!(!matches("name!=bash", &p));
+        assert!(!matches("name!=ba*", &p));
+        assert!(matches("name!=zsh", &p));
+        assert!(matches("user!=nobody", &p));
+    }
+
+    #[test]
+    fn bare_words_match_names_or_the_pid() {
+        let p = process(4242, "Xorg", GIB);
+        assert!(matches("xorg", &p));
+        assert!(matches("4242", &p));
+        assert!(!matches("424", &p));
+        assert!(Query::parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_queries_are_invalid_arguments() {
+        for input in [
+            "cpu>",
+            "cpu>abc",
+            "mem>1.5X",
+            "name>3",
+            "pid~1",
+            "size>10",
+            "name:\"unterminated",
+            "!",
+            "cpu > 20",
+            ">20",
+        ] {
+            let error = Query::parse(input).expect_err(input);
+            assert_eq!(error.kind, ErrorKind::InvalidArgument, "{}", input);
+            assert_eq!(error.operation, OPERATION);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use super::{HostReading, NetworkReading, ProcessRecord, SystemSource};
+use crate::error::CommandResult;
+use crate::models::{ComponentInfo, PortInfo, ProcessDetails};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Everything a source reports for one refresh
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SourceFrame {
+    pub interval_ms: u64, // Time the byte counters cover; 0 when unknown
+    pub host: HostReading,
+    pub network: NetworkReading,
+    pub processes: Vec<ProcessRecord>,
+    pub components: Vec<ComponentInfo>,
+    pub ports: Vec<PortInfo>,
+}
+
+impl SourceFrame {
+    /// Snapshot the current reading of any source (used to record fixtures)
+    pub fn capture(source: &dyn SystemSource) -> Self {
+        Self {
+            interval_ms: 0,
+            host: source.host(),
+            network: source.network(),
+            processes: source.processes(),
+            components: source.components(),
+            // Port listing can fail without privileges; record what is visible
+            ports: source.ports().unwrap_or_default(),
+        }
+    }
+}
+
+/// On-disk fixture format: a JSON object with the recorded frames in order
+#[derive(Serialize, Deserialize, Default)]
+pub struct Fixture {
+    pub frames: Vec<SourceFrame>,
+}
+
+/// Deterministic source replaying recorded frames. Each refresh moves to the
+/// next frame and wraps around after the last one.
+pub struct FixtureSource {
+    frames: Vec<SourceFrame>,
+    position: Option<usize>,
+}
+
+impl FixtureSource {
+    pub fn new(frames: Vec<SourceFrame>) -> Self {
+        Self {
+            frames,
+            position: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let fixture: Fixture = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if fixture.frames.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "fixture has no frames",
+            ));
+        }
+        Ok(Self::new(fixture.frames))
+    }
+
+    fn frame(&self) -> Option<&SourceFrame> {
+        self.frames.get(self.position.unwrap_or(0))
+    }
+}
+
+impl SystemSource for FixtureSource {
+    fn refresh(&mut self) {
+        if self.frames.is_empty() {
+            return;
+        }
+        self.position = Some(match self.position {
+            Some(position) => (position + 1) % self.frames.len(),
+            None => 0,
+        });
+    }
+
+    fn host(&self) -> HostReading {
+        self.frame().map(|f| f.host.clone()).unwrap_or_default()
+    }
+
+    fn processes(&self) -> Vec<ProcessRecord> {
+        self.frame()
+            .map(|f| f.processes.clone())
+            .unwrap_or_default()
+    }
+
+    fn network(&self) -> NetworkReading {
+        self.frame().map(|f| f.network.clone()).unwrap_or_default()
+    }
+
+    fn components(&self) -> Vec<ComponentInfo> {
+        self.frame()
+            .map(|f| f.components.clone())
+            .unwrap_or_default()
+    }
+
+    fn ports(&self) -> CommandResult<Vec<PortInfo>> {
+        Ok(self.frame().map(|f| f.ports.clone()).unwrap_or_default())
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        // Replays run at the monitor's pace, but rates must match the recording
+        self.frame()
+            .filter(|f| f.interval_ms > 0)
+            .map(|f| Duration::from_millis(f.interval_ms))
+    }
+
+    fn is_replay(&self) -> bool {
+        true
+    }
+
+    fn refresh_process(&mut self, pid: u32) -> bool {
+        self.frame()
+            .is_some_and(|f| f.processes.iter().any(|p| p.pid == pid))
+    }
+
+    fn process_details(&self, pid: u32) -> Option<ProcessDetails> {
+        let frame = self.frame()?;
+        let process = frame.processes.iter().find(|p| p.pid == pid)?;
+        let cpu_count = frame.host.cpu_cores.len().max(1) as f32;
+
+        // Fixtures only record what the tree needs
+        Some(ProcessDetails {
+            pid,
+            start_ticks: process.start_ticks,
+            name: process.name.clone(),
+            cmd: Vec::new(),
+            exe: process.exe.clone(),
+            user: process.user.clone(),
+            cwd: String::new(),
+            root: String::new(),
+            status: "Run".to_string(),
+            run_time: 0,
+            memory_usage: process.memory,
+            memory_breakdown: process.memory_breakdown,
+            cpu_usage: process.cpu_usage / cpu_count,
+            environ: Vec::new(),
+            scheduling: None,
+            io_priority: None,
+        })
+    }
+}
@@ -0,0 +1,349 @@
+use super::{HostReading, NetworkReading, ProcessRecord, SystemSource};
+use crate::commands::{io_priority, scheduling};
+use crate::error::{CommandError, CommandResult, ErrorKind};
+use crate::identity;
+use crate::models::{ComponentInfo, MemoryBreakdown, PortInfo, ProcessDetails};
+use crate::suspend;
+use std::collections::HashMap;
+use sysinfo::{
+    Components, Networks, Pid, Process, ProcessRefreshKind, ProcessStatus, System, UpdateKind,
+    Users,
+};
+
+/// Reads the running system through sysinfo and the listeners crate
+pub struct LiveSource {
+    sys: System,
+    networks: Networks,
+    components: Components,
+    users: Users,
+    // PID -> (start time in seconds, precise start time); re-read when the PID is new
+    // or its start second changes
+    start_ticks: HashMap<u32, (u64, Option<u64>)>,
+    // Memory breakdowns of the last refresh; empty unless enabled
+    memory_breakdown: Option<HashMap<u32, MemoryBreakdown>>,
+    // Network refresh ticker
+    refresh_tick: u64,
+}
+
+impl Default for LiveSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveSource {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new_all(),
+            networks: Networks::new_with_refreshed_list(),
+            components: Components::new_with_refreshed_list(),
+            users: Users::new_with_refreshed_list(),
+            start_ticks: HashMap::new(),
+            memory_breakdown: None,
+            refresh_tick: 0,
+        }
+    }
+}
+
+impl LiveSource {
+    fn refresh_start_ticks(&mut self) {
+        let processes = self.sys.processes();
+        self.start_ticks
+            .retain(|pid, _| processes.contains_key(&Pid::from_u32(*pid)));
+        for (pid, process) in processes {
+            let start_time = process.start_time();
+            let entry = self.start_ticks.get(&pid.as_u32());
+            if entry.is_none_or(|(seconds, _)| *seconds != start_time) {
+                self.start_ticks.insert(
+                    pid.as_u32(),
+                    (start_time, identity::start_ticks(pid.as_u32())),
+                );
+            }
+        }
+    }
+
+    fn start_ticks(&self, pid: Pid) -> Option<u64> {
+        self.start_ticks
+            .get(&pid.as_u32())
+            .and_then(|(_, ticks)| *ticks)
+    }
+
+    fn user_name(&self, process: &Process) -> String {
+        process
+            .user_id()
+            .and_then(|uid| self.users.get_user_by_id(uid))
+            .map(|user| user.name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl SystemSource for LiveSource {
+    fn refresh(&mut self) {
+        self.refresh_tick += 1;
+
+        // Refresh CPU, Memory, Processes
+        self.sys.refresh_cpu();
+        self.sys.refresh_memory();
+        // Refresh processes including Disk Usage
+        self.sys.refresh_processes_specifics(
+            sysinfo::ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                // Owner and executable don't change; read them once per process for queries
+                .with_user(UpdateKind::OnlyIfNotSet)
+                .with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        self.refresh_start_ticks();
+        if let Some(breakdowns) = &mut self.memory_breakdown {
+            *breakdowns = self
+                .sys
+                .processes()
+                .keys()
+                .filter_map(|pid| Some((pid.as_u32(), memory_breakdown(pid.as_u32())?)))
+                .collect();
+        }
+
+        // Re-scan network interfaces periodically to catch new connections (e.g., VPN, WiFi switch)
+        if self.refresh_tick.is_multiple_of(10) {
+            self.networks.refresh_list();
+            self.users.refresh_list();
+        }
+        self.networks.refresh();
+
+        self.components.refresh();
+    }
+
+    fn host(&self) -> HostReading {
+        HostReading {
+            cpu_usage: self.sys.global_cpu_info().cpu_usage(),
+            cpu_cores: self.sys.cpus().iter().map(|c| c.cpu_usage()).collect(),
+            memory_used: self.sys.used_memory(),
+            memory_total: self.sys.total_memory(),
+        }
+    }
+
+    fn processes(&self) -> Vec<ProcessRecord> {
+        let mut suspended = suspend::Probe::default();
+        self.sys
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let disk_usage = process.disk_usage();
+                let start_ticks = self.start_ticks(*pid);
+                ProcessRecord {
+                    pid: pid.as_u32(),
+                    parent: process.parent().map(|p| p.as_u32()),
+                    name: process.name().to_string(),
+                    start_time: process.start_time(),
+                    start_ticks,
+                    cpu_usage: process.cpu_usage(),
+                    memory: process.memory(),
+                    memory_breakdown: self
+                        .memory_breakdown
+                        .as_ref()
+                        .and_then(|b| b.get(&pid.as_u32()).copied()),
+                    disk_read: disk_usage.read_bytes,
+                    disk_write: disk_usage.written_bytes,
+                    thread_count: process.tasks().map(|t| t.len() as u64).unwrap_or(0),
+                    user: self.user_name(process),
+                    exe: process
+                        .exe()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    suspended: process.status() == ProcessStatus::Stop
+                        || suspended.is_suspended(pid.as_u32(), start_ticks),
+                }
+            })
+            .collect()
+    }
+
+    fn network(&self) -> NetworkReading {
+        // sysinfo::Networks::refresh() updates the data to show bytes transmitted/received
+        // SINCE THE LAST REFRESH.
+        let mut reading = NetworkReading::default();
+        for (_name, data) in &self.networks {
+            reading.transmitted += data.transmitted();
+            reading.received += data.received();
+        }
+        reading
+    }
+
+    fn components(&self) -> Vec<ComponentInfo> {
+        self.components
+            .iter()
+            .map(|c| ComponentInfo {
+                label: c.label().to_string(),
+                temperature: c.temperature(),
+                max_temperature: c.max(),
+                critical_temperature: c.critical(),
+            })
+            .collect()
+    }
+
+    fn ports(&self) -> CommandResult<Vec<PortInfo>> {
+        open_ports(&self.sys)
+    }
+
+    fn refresh_process(&mut self, pid: u32) -> bool {
+        // CPU usage is left to the periodic refresh: measuring it here would
+        // shorten the interval the next sample compares against.
+        // Processes started after the first refresh have no command line or paths yet.
+        self.sys.refresh_process_specifics(
+            Pid::from(pid as usize),
+            ProcessRefreshKind::new()
+                .with_memory()
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_environ(UpdateKind::OnlyIfNotSet)
+                .with_root(UpdateKind::OnlyIfNotSet)
+                .with_cwd(UpdateKind::Always),
+        )
+    }
+
+    fn set_memory_breakdown(&mut self, enabled: bool) {
+        if enabled != self.memory_breakdown.is_some() {
+            self.memory_breakdown = enabled.then(HashMap::new);
+        }
+    }
+
+    fn process_details(&self, pid: u32) -> Option<ProcessDetails> {
+        let process = self.sys.process(Pid::from(pid as usize))?;
+        let cpu_count = self.sys.cpus().len().max(1) as f32;
+
+        Some(ProcessDetails {
+            pid: process.pid().as_u32(),
+            start_ticks: identity::start_ticks(pid),
+            name: process.name().to_string(),
+            cmd: process.cmd().to_vec(),
+            exe: process
+                .exe()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            cwd: process
+                .cwd()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            user: self.user_name(process),
+            root: process
+                .root()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            status: format!("{:?}", process.status()),
+            run_time: process.run_time(),
+            memory_usage: process.memory(),
+            memory_breakdown: memory_breakdown(pid),
+            cpu_usage: process.cpu_usage() / cpu_count,
+            environ: process.environ().to_vec(),
+            scheduling: scheduling::read(pid).ok(),
+            io_priority: io_priority::read(pid).ok(),
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn cgroup(&self, pid: u32) -> Option<String> {
+        let text = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+        // `0::/path` on the unified hierarchy; `N:name=systemd:/path` on v1
+        let path = text
+            .lines()
+            .filter_map(|line| line.splitn(3, ':').nth(2).map(|path| (line, path)))
+            .find(|(line, _)| line.starts_with("0::") || line.contains(":name=systemd:"))
+            .map(|(_, path)| path.to_string())?;
+        (!path.is_empty()).then_some(path)
+    }
+}
+
+/// USS, PSS, shared and swapped memory from `/proc/<pid>/smaps_rollup`
+/// (Linux 4.14+). None for kernel threads and processes we may not inspect.
+#[cfg(target_os = "linux")]
+fn memory_breakdown(pid: u32) -> Option<MemoryBreakdown> {
+    let text = std::fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok()?;
+    let (mut pss, mut private, mut shared, mut swap) = (None, 0, 0, 0);
+    for line in text.lines() {
+        // `Pss:                 1234 kB`
+        let mut fields = line.split_whitespace();
+        let (Some(key), Some(kb)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Ok(kb) = kb.parse::<u64>() else {
+            continue;
+        };
+        let bytes = kb * 1024;
+        match key {
+            "Pss:" => pss = Some(bytes),
+            "Private_Clean:" | "Private_Dirty:" => private += bytes,
+            "Shared_Clean:" | "Shared_Dirty:" => shared += bytes,
+            "Swap:" => swap = bytes,
+            _ => {}
+        }
+    }
+    // Kernel threads have no mappings, so no summary
+    Some(MemoryBreakdown {
+        pss: Some(pss?),
+        uss: Some(private),
+        shared: Some(shared),
+        swap: Some(swap),
+    })
+}
+
+/// Private commit charge as USS; Windows has no cheap equivalent of the others
+#[cfg(target_os = "windows")]
+fn memory_breakdown(pid: u32) -> Option<MemoryBreakdown> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS, PROCESS_MEMORY_COUNTERS_EX,
+    };
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut counters = PROCESS_MEMORY_COUNTERS_EX::default();
+        let result = GetProcessMemoryInfo(
+            handle,
+            &mut counters as *mut PROCESS_MEMORY_COUNTERS_EX as *mut PROCESS_MEMORY_COUNTERS,
+            std::mem::size_of::<PROCESS_MEMORY_COUNTERS_EX>() as u32,
+        );
+        let _ = CloseHandle(handle);
+        result.ok()?;
+        Some(MemoryBreakdown {
+            uss: Some(counters.PrivateUsage as u64),
+            ..MemoryBreakdown::default()
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn memory_breakdown(_pid: u32) -> Option<MemoryBreakdown> {
+    None
+}
+
+/// Listening sockets, with owning process names looked up in `sys`
+pub fn open_ports(sys: &System) -> CommandResult<Vec<PortInfo>> {
+    let mut ports = Vec::new();
+
+    // Use listeners crate to get open ports
+    let listeners = listeners::get_all().map_err(|e| match e.downcast_ref::<std::io::Error>() {
+        Some(io) => CommandError::from_io("list_ports", io),
+        None => CommandError::new(ErrorKind::Io, "list_ports", e.to_string()),
+    })?;
+
+    for l in listeners {
+        let pid = l.process.pid;
+        let process_name = sys
+            .process(Pid::from(pid as usize))
+            .map(|p| p.name().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        ports.push(PortInfo {
+            pid: Some(pid),
+            start_ticks: identity::start_ticks(pid),
+            process_name,
+            port: l.socket.port(),
+            protocol: "TCP/UDP".to_string(),
+            address: l.socket.to_string(),
+        });
+    }
+
+    ports.sort_by_key(|p| p.port);
+    Ok(ports)
+}
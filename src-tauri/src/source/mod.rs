@@ -0,0 +1,121 @@
+pub mod fixture;
+pub mod live;
+
+use crate::error::CommandResult;
+use crate::models::{ComponentInfo, MemoryBreakdown, PortInfo, ProcessDetails};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub use fixture::FixtureSource;
+pub use live::LiveSource;
+
+/// Environment variable naming a recorded fixture to replay instead of the live system
+pub const FIXTURE_ENV: &str = "ACTIOWATCH_FIXTURE";
+
+/// Global CPU and memory readings
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HostReading {
+    pub cpu_usage: f32,
+    pub cpu_cores: Vec<f32>,
+    pub memory_used: u64,
+    pub memory_total: u64,
+}
+
+/// Bytes moved over all interfaces since the previous refresh
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NetworkReading {
+    pub transmitted: u64,
+    pub received: u64,
+}
+
+/// One process as seen by a source, before it is placed in the tree
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProcessRecord {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub start_time: u64, // Unix timestamp in seconds, 0 when unknown
+    #[serde(default)]
+    pub start_ticks: Option<u64>, // Precise start time (see `crate::identity`), None when unknown
+    pub cpu_usage: f32,  // Raw sysinfo value: 100% per fully used core
+    pub memory: u64,
+    #[serde(default)]
+    pub memory_breakdown: Option<MemoryBreakdown>, // Only when requested; see `set_memory_breakdown`
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub thread_count: u64,
+    #[serde(default)]
+    pub user: String, // Owner's login name, empty when unknown
+    #[serde(default)]
+    pub exe: String, // Executable path, empty when unknown
+    #[serde(default)]
+    pub suspended: bool, // Stopped, frozen or suspended (see `crate::suspend`)
+}
+
+/// Everything the monitor reads from the machine. `LiveSource` asks the OS;
+/// `FixtureSource` replays recorded frames so the monitor can run deterministically.
+pub trait SystemSource: Send {
+    /// Advance to the next reading. Rates are relative to the previous refresh.
+    fn refresh(&mut self);
+
+    fn host(&self) -> HostReading;
+
+    fn processes(&self) -> Vec<ProcessRecord>;
+
+    fn network(&self) -> NetworkReading;
+
+    fn components(&self) -> Vec<ComponentInfo>;
+
+    fn ports(&self) -> CommandResult<Vec<PortInfo>>;
+
+    /// Time covered by the last refresh when the source knows it (recordings);
+    /// otherwise the monitor measures it
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Re-read one process without touching CPU accounting. Returns false if it is gone.
+    fn refresh_process(&mut self, pid: u32) -> bool;
+
+    /// Details of one process as of the last refresh, CPU usage normalized to all cores
+    fn process_details(&self, pid: u32) -> Option<ProcessDetails>;
+
+    /// Whether following refreshes read each process's `MemoryBreakdown`. Off by default:
+    /// it costs far more than the rest of a refresh (a page table walk per process on Linux).
+    fn set_memory_breakdown(&mut self, _enabled: bool) {}
+
+    /// Whether the readings are a recording rather than the machine this runs on.
+    /// Their PIDs then belong to other processes here, so nothing may act on them.
+    fn is_replay(&self) -> bool {
+        false
+    }
+
+    /// Path of the process's control group (cgroup v2, or the systemd hierarchy of v1),
+    /// read on demand; None where there are no cgroups
+    fn cgroup(&self, _pid: u32) -> Option<String> {
+        None
+    }
+}
+
+/// The fixture named by `ACTIOWATCH_FIXTURE`, if it is set and loads. The CLI, TUI
+/// and daemon replay it; the GUI always watches the live system.
+pub fn fixture_from_env() -> Option<FixtureSource> {
+    let path = std::env::var_os(FIXTURE_ENV)?;
+    FixtureSource::load(&path)
+        .map_err(|e| {
+            eprintln!(
+                "Failed to load fixture {}: {}",
+                std::path::Path::new(&path).display(),
+                e
+            )
+        })
+        .ok()
+}
+
+/// The fixture named by `ACTIOWATCH_FIXTURE`, or the live system if there is none
+pub fn from_env() -> Box<dyn SystemSource> {
+    match fixture_from_env() {
+        Some(source) => Box::new(source),
+        None => Box::new(LiveSource::new()),
+    }
+}
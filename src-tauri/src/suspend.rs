@@ -0,0 +1,213 @@
+//! Pausing a process without ending it.
+//!
+//! - Linux: a process alone in a cgroup v2 group we may write is frozen through
+//!   `cgroup.freeze`, which it (and its parent) can't observe; any other is stopped
+//!   with SIGSTOP
+//! - Windows: `NtSuspendProcess`, which suspends every thread
+//! - elsewhere: SIGSTOP
+//!
+//! A stopped Unix process shows as such in its status and a frozen one in its
+//! cgroup's `cgroup.events`. Windows keeps no such flag, so the processes suspended
+//! there are remembered for `is_suspended`.
+
+use crate::error::{CommandError, CommandResult};
+
+pub use platform::{is_suspended, resume, suspend};
+
+#[cfg(target_os = "linux")]
+pub use platform::Probe;
+
+/// `is_suspended` for every process of a refresh
+#[cfg(not(target_os = "linux"))]
+#[derive(Default)]
+pub struct Probe;
+
+#[cfg(not(target_os = "linux"))]
+impl Probe {
+    pub fn is_suspended(&mut self, pid: u32, start_ticks: Option<u64>) -> bool {
+        is_suspended(pid, start_ticks)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use crate::commands::signal;
+    use crate::models::ProcessSignal;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    /// `is_suspended` for every process of a refresh, reading the state of each
+    /// cgroup once however many processes share it
+    #[derive(Default)]
+    pub struct Probe {
+        frozen: HashMap<PathBuf, bool>,
+    }
+
+    impl Probe {
+        pub fn is_suspended(&mut self, pid: u32, _start_ticks: Option<u64>) -> bool {
+            let Some(dir) = cgroup(pid) else {
+                return false;
+            };
+            *self.frozen.entry(dir).or_insert_with_key(|dir| frozen(dir))
+        }
+    }
+
+    /// Whether the cgroup of the process is frozen, by us or anyone else. Stopped
+    /// processes show in their status instead; `_start_ticks` is not needed as only
+    /// the current holder of `pid` is looked at.
+    pub fn is_suspended(pid: u32, _start_ticks: Option<u64>) -> bool {
+        cgroup(pid).is_some_and(|dir| frozen(&dir))
+    }
+
+    pub fn suspend(pid: u32, operation: &'static str) -> CommandResult<()> {
+        match own_cgroup(pid) {
+            Some(cgroup) => write_freeze(&cgroup, "1", operation),
+            None => signal::deliver(pid, ProcessSignal::Stop, operation),
+        }
+    }
+
+    /// Thaw the process's own cgroup if it is frozen, and continue it if it is stopped
+    pub fn resume(pid: u32, operation: &'static str) -> CommandResult<()> {
+        if let Some(cgroup) = own_cgroup(pid) {
+            let frozen = std::fs::read_to_string(cgroup.join("cgroup.freeze"))
+                .is_ok_and(|value| value.trim() == "1");
+            if frozen {
+                write_freeze(&cgroup, "0", operation)?;
+            }
+        }
+        signal::deliver(pid, ProcessSignal::Cont, operation)
+    }
+
+    /// The cgroup v2 directory of `pid` if the process is its only member and its
+    /// freezer is writable; freezing a shared group would pause the others too
+    fn own_cgroup(pid: u32) -> Option<PathBuf> {
+        let dir = cgroup(pid)?;
+        let procs = std::fs::read_to_string(dir.join("cgroup.procs")).ok()?;
+        let mut members = procs.split_whitespace();
+        if members.next()? != pid.to_string() || members.next().is_some() {
+            return None;
+        }
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(dir.join("cgroup.freeze"))
+            .ok()?;
+        Some(dir)
+    }
+
+    /// The cgroup v2 directory of `pid`
+    fn cgroup(pid: u32) -> Option<PathBuf> {
+        let text = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+        let path = text.lines().find_map(|line| line.strip_prefix("0::"))?;
+        Some(PathBuf::from("/sys/fs/cgroup").join(path.trim_start_matches('/')))
+    }
+
+    /// `frozen 1` in `cgroup.events`: every process of the group has stopped
+    fn frozen(cgroup: &Path) -> bool {
+        std::fs::read_to_string(cgroup.join("cgroup.events"))
+            .is_ok_and(|events| events.lines().any(|line| line.trim() == "frozen 1"))
+    }
+
+    fn write_freeze(cgroup: &Path, value: &str, operation: &'static str) -> CommandResult<()> {
+        std::fs::write(cgroup.join("cgroup.freeze"), value).map_err(|e| {
+            CommandError::from_io(operation, &e).context("Failed to write cgroup.freeze")
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::identity;
+    use std::sync::Mutex;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, NTSTATUS};
+    use windows::Win32::System::Threading::PROCESS_SUSPEND_RESUME;
+
+    windows::core::link!("ntdll.dll" "system" fn NtSuspendProcess(process: HANDLE) -> NTSTATUS);
+    windows::core::link!("ntdll.dll" "system" fn NtResumeProcess(process: HANDLE) -> NTSTATUS);
+
+    // Processes suspended by us, with their start time
+    static SUSPENDED: Mutex<Vec<(u32, u64)>> = Mutex::new(Vec::new());
+
+    /// Whether the process was suspended through this module and not resumed since.
+    /// Without `start_ticks` the current holder of `pid` is looked up, so a reused
+    /// PID doesn't inherit the flag.
+    pub fn is_suspended(pid: u32, start_ticks: Option<u64>) -> bool {
+        let Some(ticks) = start_ticks.or_else(|| identity::start_ticks(pid)) else {
+            return false;
+        };
+        SUSPENDED.lock().unwrap().contains(&(pid, ticks))
+    }
+
+    /// Record the change. A process whose start time can't be read is not
+    /// remembered, as it couldn't be told apart from a later one with its PID.
+    fn remember(pid: u32, suspended: bool) {
+        let mut list = SUSPENDED.lock().unwrap();
+        // Forget processes that have exited meanwhile
+        list.retain(|&(p, ticks)| p != pid && identity::is_running(p, ticks));
+        if suspended {
+            if let Some(ticks) = identity::start_ticks(pid) {
+                list.push((pid, ticks));
+            }
+        }
+    }
+
+    pub fn suspend(pid: u32, operation: &'static str) -> CommandResult<()> {
+        call(pid, operation, |handle| unsafe { NtSuspendProcess(handle) })?;
+        remember(pid, true);
+        Ok(())
+    }
+
+    pub fn resume(pid: u32, operation: &'static str) -> CommandResult<()> {
+        call(pid, operation, |handle| unsafe { NtResumeProcess(handle) })?;
+        remember(pid, false);
+        Ok(())
+    }
+
+    fn call(
+        pid: u32,
+        operation: &'static str,
+        f: impl FnOnce(HANDLE) -> NTSTATUS,
+    ) -> CommandResult<()> {
+        let handle =
+            crate::commands::process::open_process(pid, PROCESS_SUSPEND_RESUME, operation)?;
+        let status = f(handle);
+        unsafe {
+            let _ = CloseHandle(handle);
+        }
+        // NT_SUCCESS: success and informational codes are non-negative
+        if status.0 >= 0 {
+            Ok(())
+        } else {
+            Err(CommandError::new(
+                ErrorKind::Io,
+                operation,
+                format!(
+                    "Failed to suspend or resume process {}: NTSTATUS {:#010x}",
+                    pid, status.0
+                ),
+            ))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::*;
+    use crate::commands::signal;
+    use crate::models::ProcessSignal;
+
+    /// Stopped processes show in their status
+    pub fn is_suspended(_pid: u32, _start_ticks: Option<u64>) -> bool {
+        false
+    }
+
+    pub fn suspend(pid: u32, operation: &'static str) -> CommandResult<()> {
+        signal::deliver(pid, ProcessSignal::Stop, operation)
+    }
+
+    pub fn resume(pid: u32, operation: &'static str) -> CommandResult<()> {
+        signal::deliver(pid, ProcessSignal::Cont, operation)
+    }
+}
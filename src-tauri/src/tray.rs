@@ -1,5 +1,7 @@
 use crate::models::AppLifecycle;
+use crate::monitor::MonitorWaker;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::TrayIconBuilder,
@@ -32,6 +34,10 @@ fn get_or_create_window<R: Runtime>(
         Ok(window) => {
             let _ = window.show();
             let _ = window.set_focus();
+            // Leave low-cost mode now instead of after the idle sleep
+            if let Some(waker) = app.try_state::<Arc<MonitorWaker>>() {
+                waker.wake();
+            }
             Some(window)
         }
         Err(e) => {
@@ -0,0 +1,884 @@
+use crate::commands::io_priority::set_process_io_priority;
+use crate::commands::process::{
+    process_affinity, resume_process, set_process_affinity, set_process_priority, suspend_process,
+};
+use crate::commands::signal::{self, DEFAULT_GRACE};
+use crate::models::{
+    IoClass, IoPriority, ProcessInfo, SystemStats, TerminateProgress, TerminateStage,
+};
+use crate::monitor::view::{ProcessView, ViewMode, ViewRow};
+use crate::monitor::{set_totals, Monitor};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table, TableState,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+// How often the status line follows a running termination
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Same labels the GUI passes to `set_process_priority`
+const PRIORITIES: [&str; 6] = [
+    "Realtime",
+    "High",
+    "Above Normal",
+    "Normal",
+    "Below Normal",
+    "Low",
+];
+
+/// How `PRIORITIES[index]` is shown; on Linux all but Realtime are nice values
+fn priority_label(index: usize) -> &'static str {
+    #[cfg(target_os = "linux")]
+    const LABELS: [&str; 6] = [
+        "Realtime (round robin)",
+        "High (nice -10)",
+        "Above Normal (nice -5)",
+        "Normal (nice 0)",
+        "Below Normal (nice 5)",
+        "Low (nice 19)",
+    ];
+    #[cfg(not(target_os = "linux"))]
+    const LABELS: [&str; 6] = PRIORITIES;
+    LABELS[index]
+}
+
+// I/O priorities offered by `i`, as (label, class, level)
+const IO_PRIORITIES: [(&str, IoClass, u8); 5] = [
+    ("Realtime", IoClass::Realtime, 4),
+    ("High", IoClass::BestEffort, 0),
+    ("Normal", IoClass::BestEffort, 4),
+    ("Low", IoClass::BestEffort, 7),
+    ("Idle", IoClass::Idle, 0),
+];
+
+enum Mode {
+    Normal,
+    Search,
+    // `ticks` is the start time the process was selected with (see `crate::identity`)
+    ConfirmKill {
+        pid: u32,
+        ticks: Option<u64>,
+        name: String,
+    },
+    Priority {
+        pid: u32,
+        ticks: Option<u64>,
+        list: ListState,
+    },
+    IoPriority {
+        pid: u32,
+        ticks: Option<u64>,
+        list: ListState,
+    },
+    Affinity {
+        pid: u32,
+        ticks: Option<u64>,
+        cpus: Vec<bool>,
+        list: ListState,
+    },
+}
+
+/// A process being ended on a background thread; progress and the final error (if
+/// any) arrive on `updates`, which disconnects once it is over
+struct Termination {
+    pid: u32,
+    name: String,
+    updates: Receiver<Result<TerminateProgress, String>>,
+}
+
+/// One line of the process table
+struct VisibleRow {
+    depth: usize,
+    has_children: bool,
+    process: ProcessInfo,
+}
+
+impl VisibleRow {
+    /// Copy a view row without its children
+    fn new(row: &ViewRow) -> Self {
+        let node = row.process;
+        Self {
+            depth: row.depth,
+            has_children: !node.children.is_empty(),
+            process: ProcessInfo {
+                pid: node.pid,
+                name: node.name.clone(),
+                start_time: node.start_time,
+                start_ticks: node.start_ticks,
+                user: node.user.clone(),
+                exe: node.exe.clone(),
+                cpu_usage: node.cpu_usage,
+                total_cpu_usage: node.total_cpu_usage,
+                memory_usage: node.memory_usage,
+                total_memory_usage: node.total_memory_usage,
+                memory_breakdown: node.memory_breakdown,
+                disk_read: node.disk_read,
+                disk_write: node.disk_write,
+                total_disk_read: node.total_disk_read,
+                total_disk_write: node.total_disk_write,
+                thread_count: node.thread_count,
+                suspended: node.suspended,
+                children: Vec::new(),
+            },
+        }
+    }
+}
+
+struct App {
+    monitor: Monitor,
+    stats: Option<SystemStats>,
+    rows: Vec<VisibleRow>,
+    table: TableState,
+    view: ProcessView,
+    mode: Mode,
+    status: Option<String>,
+    termination: Option<Termination>,
+}
+
+/// Run the terminal UI until the user quits and return the process exit code
+pub fn run() -> i32 {
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("Failed to initialize terminal: {}", e);
+            return 1;
+        }
+    };
+
+    let result = App::new().run(&mut terminal);
+    ratatui::restore();
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1_073_741_824.0 {
+        format!("{:.2} GB", bytes / 1_073_741_824.0)
+    } else if bytes >= 1_048_576.0 {
+        format!("{:.1} MB", bytes / 1_048_576.0)
+    } else {
+        format!("{:.1} KB", bytes / 1024.0)
+    }
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            monitor: Monitor::new(),
+            stats: None,
+            rows: Vec::new(),
+            table: TableState::default().with_selected(Some(0)),
+            view: ProcessView {
+                mode: ViewMode::Tree,
+                ..ProcessView::default()
+            },
+            mode: Mode::Normal,
+            status: None,
+            termination: None,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        // First sample only primes CPU usage; wait the minimum interval before the visible one
+        self.monitor.sample();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        self.refresh();
+        let mut last_refresh = Instant::now();
+
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let mut timeout = REFRESH_INTERVAL.saturating_sub(last_refresh.elapsed());
+            if self.termination.is_some() {
+                timeout = timeout.min(PROGRESS_INTERVAL);
+            }
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            self.follow_termination();
+            if last_refresh.elapsed() >= REFRESH_INTERVAL {
+                self.refresh();
+                last_refresh = Instant::now();
+            }
+        }
+    }
+
+    /// Ask the process to exit and kill it after `DEFAULT_GRACE`, like the GUI's
+    /// `terminate_process`, without blocking the UI
+    fn terminate(&mut self, pid: u32, ticks: Option<u64>, name: String) {
+        let (sender, updates) = mpsc::channel();
+        std::thread::spawn(move || {
+            let progress = |update: &TerminateProgress| {
+                let _ = sender.send(Ok(update.clone()));
+            };
+            if let Err(e) = signal::terminate(pid, ticks, DEFAULT_GRACE, progress) {
+                let _ = sender.send(Err(e.to_string()));
+            }
+        });
+        self.status = Some(format!("Ending {} ({})", name, pid));
+        self.termination = Some(Termination { pid, name, updates });
+    }
+
+    /// Show the latest progress of the running termination in the status line
+    fn follow_termination(&mut self) {
+        let Some(termination) = &self.termination else {
+            return;
+        };
+        let (pid, name) = (termination.pid, &termination.name);
+        loop {
+            let update = match termination.updates.try_recv() {
+                Ok(update) => update,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            };
+            self.status = Some(match update {
+                Ok(progress) => match progress.stage {
+                    TerminateStage::Requested | TerminateStage::Waiting => format!(
+                        "Asked {} ({}) to exit, killing it in {:.0} s",
+                        name,
+                        pid,
+                        progress.grace_ms.saturating_sub(progress.elapsed_ms) as f64 / 1000.0
+                    ),
+                    TerminateStage::Killing => {
+                        format!("{} ({}) is still running, killing it", name, pid)
+                    }
+                    TerminateStage::Exited => format!("{} ({}) exited", name, pid),
+                    TerminateStage::Killed => format!("Killed {} ({})", name, pid),
+                },
+                Err(e) => e,
+            });
+        }
+        self.termination = None;
+    }
+
+    fn refresh(&mut self) {
+        self.monitor
+            .set_memory_breakdown(self.view.wants_memory_breakdown());
+        self.stats = Some(self.monitor.sample());
+        self.rebuild_rows();
+    }
+
+    fn selected_pid(&self) -> Option<u32> {
+        self.selected_identity().map(|(pid, _)| pid)
+    }
+
+    /// PID and start ticks of the selected process, for commands that act on it
+    fn selected_identity(&self) -> Option<(u32, Option<u64>)> {
+        self.table
+            .selected()
+            .and_then(|i| self.rows.get(i))
+            .map(|r| (r.process.pid, r.process.start_ticks))
+    }
+
+    /// Recompute the visible rows from the latest sample, keeping the selected process selected
+    fn rebuild_rows(&mut self) {
+        let selected = self.selected_pid();
+        let Some(stats) = &mut self.stats else {
+            return;
+        };
+        set_totals(&mut stats.top_processes, self.view.aggregate);
+
+        let rows = self
+            .view
+            .rows(&stats.top_processes)
+            .iter()
+            .map(VisibleRow::new)
+            .collect();
+
+        self.rows = rows;
+        let index = selected
+            .and_then(|pid| self.rows.iter().position(|r| r.process.pid == pid))
+            .unwrap_or(0)
+            .min(self.rows.len().saturating_sub(1));
+        self.table.select(Some(index));
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.rows.len() as isize - 1);
+        self.table.select(Some(next as usize));
+    }
+
+    /// Returns false when the app should quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        // A message lasts until the next key, which brings the help back (unless it
+        // sets a new one); a running termination keeps reporting its progress
+        if self.termination.is_none() {
+            self.status = None;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => return self.handle_normal_key(key),
+            Mode::Search => match key.code {
+                KeyCode::Esc => {
+                    self.view.filter.clear();
+                    self.rebuild_rows();
+                }
+                KeyCode::Enter => {}
+                KeyCode::Backspace => {
+                    self.view.filter.pop();
+                    self.rebuild_rows();
+                    self.mode = Mode::Search;
+                }
+                KeyCode::Char(c) => {
+                    self.view.filter.push(c);
+                    self.rebuild_rows();
+                    self.mode = Mode::Search;
+                }
+                _ => self.mode = Mode::Search,
+            },
+            Mode::ConfirmKill { pid, ticks, name } => {
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) {
+                    self.terminate(pid, ticks, name);
+                }
+            }
+            Mode::Priority {
+                pid,
+                ticks,
+                mut list,
+            } => match key.code {
+                KeyCode::Up => {
+                    list.select_previous();
+                    self.mode = Mode::Priority { pid, ticks, list };
+                }
+                KeyCode::Down => {
+                    list.select_next();
+                    self.mode = Mode::Priority { pid, ticks, list };
+                }
+                KeyCode::Enter => {
+                    let index = list.selected().unwrap_or(3).min(PRIORITIES.len() - 1);
+                    self.status = Some(
+                        match set_process_priority(pid, ticks, PRIORITIES[index].to_string()) {
+                            Ok(_) => {
+                                format!("Priority of {} set to {}", pid, priority_label(index))
+                            }
+                            Err(e) => e.to_string(),
+                        },
+                    );
+                }
+                KeyCode::Esc => {}
+                _ => self.mode = Mode::Priority { pid, ticks, list },
+            },
+            Mode::IoPriority {
+                pid,
+                ticks,
+                mut list,
+            } => match key.code {
+                KeyCode::Up => {
+                    list.select_previous();
+                    self.mode = Mode::IoPriority { pid, ticks, list };
+                }
+                KeyCode::Down => {
+                    list.select_next();
+                    self.mode = Mode::IoPriority { pid, ticks, list };
+                }
+                KeyCode::Enter => {
+                    let index = list.selected().unwrap_or(2).min(IO_PRIORITIES.len() - 1);
+                    let (label, class, level) = IO_PRIORITIES[index];
+                    self.status = Some(
+                        match set_process_io_priority(pid, ticks, IoPriority { class, level }) {
+                            Ok(_) => format!("I/O priority of {} set to {}", pid, label),
+                            Err(e) => e.to_string(),
+                        },
+                    );
+                }
+                KeyCode::Esc => {}
+                _ => self.mode = Mode::IoPriority { pid, ticks, list },
+            },
+            Mode::Affinity {
+                pid,
+                ticks,
+                mut cpus,
+                mut list,
+            } => match key.code {
+                KeyCode::Up => {
+                    list.select_previous();
+                    self.mode = Mode::Affinity {
+                        pid,
+                        ticks,
+                        cpus,
+                        list,
+                    };
+                }
+                KeyCode::Down => {
+                    list.select_next();
+                    self.mode = Mode::Affinity {
+                        pid,
+                        ticks,
+                        cpus,
+                        list,
+                    };
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(cpu) = list.selected().and_then(|i| cpus.get_mut(i)) {
+                        *cpu = !*cpu;
+                    }
+                    self.mode = Mode::Affinity {
+                        pid,
+                        ticks,
+                        cpus,
+                        list,
+                    };
+                }
+                KeyCode::Char('a') => {
+                    let all = cpus.iter().all(|c| *c);
+                    cpus.iter_mut().for_each(|c| *c = !all);
+                    self.mode = Mode::Affinity {
+                        pid,
+                        ticks,
+                        cpus,
+                        list,
+                    };
+                }
+                KeyCode::Enter => {
+                    let selected: Vec<u32> = cpus
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, on)| **on)
+                        .map(|(i, _)| i as u32)
+                        .collect();
+                    self.status = Some(match set_process_affinity(pid, ticks, selected) {
+                        Ok(_) => format!("Affinity of {} updated", pid),
+                        Err(e) => e.to_string(),
+                    });
+                }
+                KeyCode::Esc => {}
+                _ => {
+                    self.mode = Mode::Affinity {
+                        pid,
+                        ticks,
+                        cpus,
+                        list,
+                    }
+                }
+            },
+        }
+        true
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> bool {
+        let acts = matches!(
+            key.code,
+            KeyCode::Char('x' | 'z' | 'n' | 'i' | 'a') | KeyCode::Delete
+        );
+        if acts && self.monitor.is_replay() {
+            self.status = Some("Replaying a fixture: process actions are disabled".to_string());
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::Home => self.move_selection(isize::MIN / 2),
+            KeyCode::End => self.move_selection(isize::MAX / 2),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('t') => {
+                self.view.mode = match self.view.mode {
+                    ViewMode::Tree => ViewMode::Flat,
+                    ViewMode::Flat => ViewMode::Tree,
+                };
+                self.rebuild_rows();
+            }
+            KeyCode::Char('s') => {
+                self.view.sort = self.view.sort.next();
+                self.view.descending = self.view.sort.descending_by_default();
+                self.rebuild_rows();
+            }
+            KeyCode::Char('r') => {
+                self.view.descending = !self.view.descending;
+                self.rebuild_rows();
+            }
+            KeyCode::Char('c') => {
+                self.view.aggregate = !self.view.aggregate;
+                self.rebuild_rows();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => {
+                if let Some(pid) = self.selected_pid() {
+                    let collapse = match key.code {
+                        KeyCode::Left => true,
+                        KeyCode::Right => false,
+                        _ => !self.view.is_collapsed(pid),
+                    };
+                    if collapse {
+                        self.view.collapsed.insert(pid);
+                    } else {
+                        self.view.collapsed.remove(&pid);
+                    }
+                    self.rebuild_rows();
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                if let Some(row) = self.table.selected().and_then(|i| self.rows.get(i)) {
+                    self.mode = Mode::ConfirmKill {
+                        pid: row.process.pid,
+                        ticks: row.process.start_ticks,
+                        name: row.process.name.clone(),
+                    };
+                }
+            }
+            KeyCode::Char('z') => {
+                if let Some(row) = self.table.selected().and_then(|i| self.rows.get(i)) {
+                    let p = &row.process;
+                    self.status = Some(if p.suspended {
+                        match resume_process(p.pid, p.start_ticks) {
+                            Ok(_) => format!("Resumed {} ({})", p.name, p.pid),
+                            Err(e) => e.to_string(),
+                        }
+                    } else {
+                        match suspend_process(p.pid, p.start_ticks) {
+                            Ok(_) => format!("Suspended {} ({})", p.name, p.pid),
+                            Err(e) => e.to_string(),
+                        }
+                    });
+                }
+            }
+            KeyCode::Char('n') => {
+                if let Some((pid, ticks)) = self.selected_identity() {
+                    self.mode = Mode::Priority {
+                        pid,
+                        ticks,
+                        list: ListState::default().with_selected(Some(3)),
+                    };
+                }
+            }
+            KeyCode::Char('i') => {
+                if let Some((pid, ticks)) = self.selected_identity() {
+                    self.mode = Mode::IoPriority {
+                        pid,
+                        ticks,
+                        list: ListState::default().with_selected(Some(2)),
+                    };
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some((pid, ticks)) = self.selected_identity() {
+                    let cpu_count = self.stats.as_ref().map_or(0, |s| s.cpu_cores.len());
+                    match process_affinity(pid, cpu_count) {
+                        Ok(current) => {
+                            let cpus = (0..cpu_count)
+                                .map(|i| current.contains(&(i as u32)))
+                                .collect();
+                            self.mode = Mode::Affinity {
+                                pid,
+                                ticks,
+                                cpus,
+                                list: ListState::default().with_selected(Some(0)),
+                            };
+                        }
+                        Err(e) => self.status = Some(e.to_string()),
+                    }
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, table, footer] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        self.draw_header(frame, header);
+        self.draw_table(frame, table);
+        self.draw_footer(frame, footer);
+        self.draw_popup(frame);
+    }
+
+    fn draw_header(&self, frame: &mut Frame, area: Rect) {
+        let Some(stats) = &self.stats else {
+            return;
+        };
+
+        let [cpu_area, memory_area, info_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ])
+        .areas(area);
+
+        let cpu = (stats.cpu_usage as f64 / 100.0).clamp(0.0, 1.0);
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Cyan))
+                .ratio(cpu)
+                .label(format!("CPU {:.1}%", stats.cpu_usage)),
+            cpu_area,
+        );
+
+        let memory = if stats.memory_total == 0 {
+            0.0
+        } else {
+            stats.memory_used as f64 / stats.memory_total as f64
+        };
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Magenta))
+                .ratio(memory.clamp(0.0, 1.0))
+                .label(format!(
+                    "Memory {} / {}",
+                    format_bytes(stats.memory_used),
+                    format_bytes(stats.memory_total)
+                )),
+            memory_area,
+        );
+
+        let info = Line::from(vec![
+            Span::raw(format!("Processes: {}  ", stats.process_count)),
+            Span::raw(format!(
+                "Net ↑ {}/s ↓ {}/s  ",
+                format_bytes(stats.network_up),
+                format_bytes(stats.network_down)
+            )),
+            Span::raw(format!(
+                "Disk R {}/s W {}/s  ",
+                format_bytes(stats.disk_read),
+                format_bytes(stats.disk_write)
+            )),
+            Span::styled(
+                format!(
+                    "Sort: {} {}  View: {}{}",
+                    self.view.sort.label(),
+                    if self.view.descending { "▼" } else { "▲" },
+                    if self.view.mode == ViewMode::Tree {
+                        "Tree"
+                    } else {
+                        "List"
+                    },
+                    if self.view.aggregate {
+                        " (subtree totals)"
+                    } else {
+                        ""
+                    }
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(info), info_area);
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        // Sorting by PSS, USS or swap shows that figure in the memory column
+        let breakdown = self.view.sort.needs_memory_breakdown();
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let p = &row.process;
+                let marker = if self.view.mode == ViewMode::Flat || !row.has_children {
+                    "  "
+                } else if self.view.is_collapsed(p.pid) && self.view.filter.is_empty() {
+                    "▸ "
+                } else {
+                    "▾ "
+                };
+
+                let suspended = if p.suspended { " [suspended]" } else { "" };
+
+                Row::new(vec![
+                    Cell::from(p.pid.to_string()),
+                    Cell::from(format!(
+                        "{}{}{}{}",
+                        "  ".repeat(row.depth),
+                        marker,
+                        p.name,
+                        suspended
+                    )),
+                    Cell::from(format!("{:.1}", p.total_cpu_usage)),
+                    Cell::from(if breakdown {
+                        self.view
+                            .sort
+                            .breakdown_value(p)
+                            .map(format_bytes)
+                            .unwrap_or_else(|| "-".to_string())
+                    } else {
+                        format_bytes(p.total_memory_usage)
+                    }),
+                    Cell::from(format_bytes(p.total_disk_read)),
+                    Cell::from(format_bytes(p.total_disk_write)),
+                    Cell::from(p.thread_count.to_string()),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Min(20),
+                Constraint::Length(6),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(7),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "PID".to_string(),
+                "NAME".to_string(),
+                "CPU%".to_string(),
+                if breakdown {
+                    self.view.sort.label().to_uppercase()
+                } else {
+                    "MEMORY".to_string()
+                },
+                "READ/S".to_string(),
+                "WRITE/S".to_string(),
+                "THR".to_string(),
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(" ActioWatch "))
+        .row_highlight_style(Style::default().bg(Color::DarkGray));
+
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let text = match &self.mode {
+            // Until the query parses the table stays unfiltered; say why
+            Mode::Search => match self.view.query() {
+                Ok(_) => format!(
+                    "Search: {}█  (Enter to keep, Esc to clear)",
+                    self.view.filter
+                ),
+                Err(e) => format!("Search: {}█  ({})", self.view.filter, e),
+            },
+            _ => match &self.status {
+                Some(status) => status.clone(),
+                None => {
+                    let filter = if self.view.filter.is_empty() {
+                        String::new()
+                    } else {
+                        format!("[filter: {}]  ", self.view.filter)
+                    };
+                    format!(
+                        "{}q quit  / search  t tree/list  s sort  r reverse  c totals  Enter fold  x end  z suspend  n priority  i I/O priority  a affinity",
+                        filter
+                    )
+                }
+            },
+        };
+        frame.render_widget(
+            Paragraph::new(text).style(Style::default().fg(Color::Gray)),
+            area,
+        );
+    }
+
+    fn draw_popup(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        match &mut self.mode {
+            Mode::ConfirmKill { pid, name, .. } => {
+                let popup = centered(area, 50, 6);
+                frame.render_widget(Clear, popup);
+                frame.render_widget(
+                    Paragraph::new(format!(
+                        "End {} ({})?\nIt is asked to exit first, and killed after {} s.\n\ny = yes, any other key = cancel",
+                        name,
+                        pid,
+                        DEFAULT_GRACE.as_secs()
+                    ))
+                    .block(Block::bordered().title(" End Process ")),
+                    popup,
+                );
+            }
+            Mode::Priority { pid, list, .. } => {
+                let popup = centered(area, 30, PRIORITIES.len() as u16 + 2);
+                let items: Vec<ListItem> = (0..PRIORITIES.len())
+                    .map(|i| ListItem::new(priority_label(i)))
+                    .collect();
+                frame.render_widget(Clear, popup);
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(Block::bordered().title(format!(" Priority of {} ", pid)))
+                        .highlight_style(Style::default().bg(Color::DarkGray)),
+                    popup,
+                    list,
+                );
+            }
+            Mode::IoPriority { pid, list, .. } => {
+                let popup = centered(area, 30, IO_PRIORITIES.len() as u16 + 2);
+                let items: Vec<ListItem> = IO_PRIORITIES
+                    .iter()
+                    .map(|(label, ..)| ListItem::new(*label))
+                    .collect();
+                frame.render_widget(Clear, popup);
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(Block::bordered().title(format!(" I/O Priority of {} ", pid)))
+                        .highlight_style(Style::default().bg(Color::DarkGray)),
+                    popup,
+                    list,
+                );
+            }
+            Mode::Affinity {
+                pid, cpus, list, ..
+            } => {
+                let popup = centered(area, 34, (cpus.len() as u16 + 3).min(20));
+                let items: Vec<ListItem> = cpus
+                    .iter()
+                    .enumerate()
+                    .map(|(i, on)| {
+                        ListItem::new(format!("[{}] CPU {}", if *on { "x" } else { " " }, i))
+                    })
+                    .collect();
+                frame.render_widget(Clear, popup);
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(
+                            Block::bordered()
+                                .title(format!(" Affinity of {} ", pid))
+                                .title_bottom(" Space toggle  a all  Enter apply "),
+                        )
+                        .highlight_style(Style::default().bg(Color::DarkGray)),
+                    popup,
+                    list,
+                );
+            }
+            Mode::Normal | Mode::Search => {}
+        }
+    }
+}
@@ -0,0 +1,43 @@
+export type CommandErrorKind =
+  | 'not_found'
+  | 'permission_denied'
+  | 'unsupported'
+  | 'invalid_argument'
+  | 'elevation_cancelled'
+  | 'io';
+
+/**
+ * Error object rejected by backend commands
+ */
+export interface CommandError {
+  kind: CommandErrorKind;
+  operation: string;
+  code: number | null;
+  message: string;
+}
+
+/**
+ * Check whether a rejected invoke carries a structured command error
+ */
+export function isCommandError(error: unknown): error is CommandError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+/**
+ * Check whether a rejected invoke failed with the given kind
+ */
+export function isErrorKind(error: unknown, kind: CommandErrorKind): boolean {
+  return isCommandError(error) && error.kind === kind;
+}
+
+/**
+ * Human readable message for any rejected invoke
+ * @returns The message, with a hint for permission errors
+ */
+export function errorMessage(error: unknown): string {
+  if (!isCommandError(error)) return String(error);
+  if (error.kind === 'permission_denied') {
+    return `${error.message}. Run ActioWatch with elevated privileges to manage this process.`;
+  }
+  return error.message;
+}
@@ -0,0 +1,26 @@
+export type IoClass = 'none' | 'realtime' | 'best_effort' | 'idle';
+
+// `level` 0 (highest) to 7 applies to realtime and best_effort; none follows the
+// nice value. Windows only has hints: idle is Very Low, best_effort 5-7 Low,
+// 0-4 Normal and realtime High.
+export interface IoPriority {
+  class: IoClass;
+  level: number;
+}
+
+export const ioPriorities: { label: string; priority: IoPriority }[] = [
+  { label: 'Realtime', priority: { class: 'realtime', level: 4 } },
+  { label: 'High', priority: { class: 'best_effort', level: 0 } },
+  { label: 'Normal', priority: { class: 'best_effort', level: 4 } },
+  { label: 'Low', priority: { class: 'best_effort', level: 7 } },
+  { label: 'Idle', priority: { class: 'idle', level: 0 } },
+];
+
+export function ioPriorityLabel(priority: IoPriority): string {
+  switch (priority.class) {
+    case 'none': return `Default (${priority.level}, from nice)`;
+    case 'realtime': return `Realtime ${priority.level}`;
+    case 'best_effort': return `Best effort ${priority.level}`;
+    case 'idle': return 'Idle';
+  }
+}
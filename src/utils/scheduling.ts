@@ -0,0 +1,33 @@
+export type SchedPolicy = 'other' | 'batch' | 'idle' | 'fifo' | 'rr' | 'deadline';
+
+// Nanoseconds: `runtime` of CPU in every `period`, within `deadline` of its start
+export interface DeadlineParams {
+  runtime: number;
+  deadline: number;
+  period: number;
+}
+
+// `nice` applies to other and batch, `rt_priority` (1-99) to fifo and rr
+export interface Scheduling {
+  policy: SchedPolicy;
+  nice: number;
+  rt_priority: number;
+  deadline: DeadlineParams | null;
+}
+
+export const policies: { value: SchedPolicy; label: string; description: string }[] = [
+  { value: 'other', label: 'Normal (OTHER)', description: 'Default time-sharing, weighted by nice value' },
+  { value: 'batch', label: 'Batch (BATCH)', description: 'CPU-bound work that should not preempt interactive tasks' },
+  { value: 'idle', label: 'Idle (IDLE)', description: 'Runs only when nothing else wants the CPU' },
+  { value: 'fifo', label: 'Real-time FIFO', description: 'Runs until it blocks or a higher priority task is ready' },
+  { value: 'rr', label: 'Real-time Round Robin', description: 'Like FIFO, with time slices between equal priorities' },
+  { value: 'deadline', label: 'Deadline (DEADLINE)', description: 'Guaranteed runtime in every period' },
+];
+
+export function policyLabel(policy: SchedPolicy): string {
+  return policies.find(p => p.value === policy)?.label ?? policy;
+}
+
+export function isRealtime(policy: SchedPolicy): boolean {
+  return policy === 'fifo' || policy === 'rr';
+}
@@ -0,0 +1,149 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/**
+ * Memory figures finer than RSS; null where the platform can't provide them
+ */
+export interface MemoryBreakdown {
+  uss: number | null;
+  pss: number | null;
+  shared: number | null;
+  swap: number | null;
+}
+
+export interface ProcessRow {
+  pid: number;
+  parent: number | null;
+  depth: number;
+  child_count: number;
+  name: string;
+  start_time: number;
+  start_ticks: number | null; // With `pid`, identifies the process; pass it to commands taking a PID
+  cpu_usage: number;
+  total_cpu_usage: number;
+  memory_usage: number;
+  total_memory_usage: number;
+  memory_breakdown: MemoryBreakdown | null; // Only with `memory_breakdown` in the view
+  disk_read: number;
+  disk_write: number;
+  total_disk_read: number;
+  total_disk_write: number;
+  thread_count: number;
+  suspended: boolean; // Stopped, frozen or suspended
+}
+
+export type SortKey = 'cpu' | 'memory' | 'pss' | 'uss' | 'swap' | 'disk_read' | 'disk_write' | 'threads' | 'name' | 'pid' | 'start_time';
+
+/**
+ * Sort, filter, shape and window of the process rows the backend streams
+ */
+export interface ProcessView {
+  sort: SortKey;
+  descending: boolean;
+  filter: string;
+  mode: 'flat' | 'tree';
+  collapsed: number[];
+  collapse_all: boolean;
+  aggregate: boolean; // Sum descendants into the total_* values
+  memory_breakdown: boolean; // Read USS/PSS/shared/swap per process (costly)
+  offset: number;
+  limit: number | null;
+}
+
+/**
+ * Payload of `stats-update` and `get_stats_snapshot`
+ */
+export interface StatsFrame {
+  seq: number;
+  kind: 'snapshot' | 'delta';
+  stats: Record<string, any>;
+  total_rows: number;
+  added: ProcessRow[];
+  removed: number[];
+  changed: (Partial<ProcessRow> & { pid: number })[];
+  order?: number[];
+}
+
+/**
+ * Rebuilds the stats and the visible process rows from numbered frames.
+ * Deltas must arrive in order; on a gap the view is refetched with `get_stats_snapshot`.
+ */
+export class StatsStream {
+  private seq: number | null = null;
+  private rows = new Map<number, ProcessRow>();
+  private order: number[] = [];
+  // Snapshot requests in flight
+  private loading = 0;
+  // Deltas that arrive while a snapshot is on its way
+  private pending: StatsFrame[] = [];
+
+  constructor(private onUpdate: (stats: Record<string, any>) => void) {}
+
+  /**
+   * Load the current table; call once the event listener is in place
+   */
+  async resync() {
+    if (this.loading > 0) return;
+    try {
+      await this.load(() => invoke<StatsFrame>('get_stats_snapshot'));
+    } catch (e) {
+      console.error('Failed to fetch stats snapshot:', e);
+    }
+  }
+
+  /**
+   * Ask the backend for another view of the process table.
+   * Rejects with the command error if the filter query is invalid; the old view stays.
+   */
+  async subscribe(view: ProcessView) {
+    await this.load(() => invoke<StatsFrame>('subscribe_process_view', { view }));
+  }
+
+  private async load(request: () => Promise<StatsFrame>) {
+    this.loading++;
+    try {
+      const snapshot = await request();
+      // A newer snapshot may have overtaken this one
+      if (this.seq === null || snapshot.seq >= this.seq) this.apply(snapshot);
+    } finally {
+      this.loading--;
+      if (this.loading === 0) {
+        const queued = this.pending;
+        this.pending = [];
+        for (const frame of queued) this.receive(frame);
+      }
+    }
+  }
+
+  /**
+   * Handle a frame from `stats-update`
+   */
+  receive(frame: StatsFrame) {
+    if (this.loading > 0) {
+      this.pending.push(frame);
+      return;
+    }
+    // Already covered by the snapshot
+    if (this.seq !== null && frame.seq <= this.seq) return;
+    if (this.seq === null || frame.seq !== this.seq + 1) {
+      this.pending.push(frame);
+      this.resync();
+      return;
+    }
+    this.apply(frame);
+  }
+
+  private apply(frame: StatsFrame) {
+    if (frame.kind === 'snapshot') this.rows.clear();
+    for (const pid of frame.removed) this.rows.delete(pid);
+    for (const row of frame.added) this.rows.set(row.pid, row);
+    for (const change of frame.changed) {
+      const row = this.rows.get(change.pid);
+      if (row) Object.assign(row, change);
+    }
+    if (frame.order) this.order = frame.order;
+    this.seq = frame.seq;
+
+    const rows = this.order.map((pid) => this.rows.get(pid)).filter((row): row is ProcessRow => !!row);
+    this.onUpdate({ ...frame.stats, top_processes: rows, total_rows: frame.total_rows });
+  }
+}